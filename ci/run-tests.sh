@@ -1,7 +1,7 @@
 #!/bin/bash
 
 cargo test \
-    --features "test-programs/test_programs" \
+    --features "test-programs/test_programs cranelift-tools/serve" \
     --workspace \
     --exclude 'wasmtime-wasi-*' \
     --exclude wasi-crypto \
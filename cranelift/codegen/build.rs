@@ -36,14 +36,18 @@ fn main() {
         })
         .collect::<Vec<_>>();
 
-    let isas = if isa_targets.is_empty() {
+    let isas = if !isa_targets.is_empty() {
+        isa_targets
+    } else if env::var("CARGO_FEATURE_HOST_ARCH").is_ok() {
         // Try to match native target.
         let target_name = target_triple.split('-').next().unwrap();
         let isa = meta::isa_from_arch(&target_name).expect("error when identifying target");
         println!("cargo:rustc-cfg=feature=\"{}\"", isa);
         vec![isa]
     } else {
-        isa_targets
+        // No backend at all: only the IR and target-independent parts of the
+        // crate are built.
+        vec![]
     };
 
     let cur_dir = env::current_dir().expect("Can't access current working directory");
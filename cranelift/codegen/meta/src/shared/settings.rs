@@ -175,6 +175,26 @@ pub(crate) fn define() -> SettingGroup {
         vec!["none", "elf_gd", "macho", "coff"],
     );
 
+    settings.add_enum(
+        "code_model",
+        "Defines how far away referenced code and data may be placed.",
+        r#"
+            This decides whether calls and symbol references use PC-relative
+            sequences, which have a limited reach (e.g. +/-2GB on x86_64 and
+            +/-128MB for AArch64 calls), or absolute/GOT sequences that can
+            reach anywhere in the address space.
+
+            - `small`: references to colocated functions and symbols are
+              PC-relative; everything else uses the far sequences.
+            - `medium`: calls to colocated functions are PC-relative, but
+              symbol references always use the far sequences, so data can be
+              placed anywhere.
+            - `large`: all calls and symbol references use the far sequences,
+              regardless of the `colocated` flag and `use_colocated_libcalls`.
+        "#,
+        vec!["small", "medium", "large"],
+    );
+
     settings.add_enum(
         "libcall_call_conv",
         "Defines the calling convention to use for LibCalls call expansion.",
@@ -256,6 +276,18 @@ pub(crate) fn define() -> SettingGroup {
         false,
     );
 
+    settings.add_bool(
+        "align_loops",
+        "Align loop headers to the target's preferred alignment.",
+        r#"
+            Blocks that are the target of a backward branch are padded with
+            NOPs up to the alignment the target prefers for them, which can
+            help instruction fetch on some cores at the cost of code size.
+            Targets without such a preference ignore this setting.
+        "#,
+        false,
+    );
+
     // Stack probing options.
 
     settings.add_bool(
@@ -108,11 +108,14 @@ impl fmt::Display for Reloc {
 
 /// Container for information about a vector of compiled code and its supporting read-only data.
 ///
-/// The code starts at offset 0 and is followed optionally by relocatable jump tables and copyable
-/// (raw binary) read-only data.  Any padding between sections is always part of the section that
-/// precedes the boundary between the sections.
+/// The code starts at offset 0. Constants are placed in islands within the code or after its
+/// end, and jump tables are emitted inline with the branches that use them, so the whole
+/// `total_size` bytes must be copied into a single executable allocation.
 #[derive(Debug, PartialEq)]
 pub struct CodeInfo {
     /// Number of bytes in total.
     pub total_size: CodeOffset,
+
+    /// Number of bytes, out of `total_size`, taken up by constants and their alignment padding.
+    pub rodata_size: CodeOffset,
 }
@@ -27,7 +27,7 @@ use crate::simple_gvn::do_simple_gvn;
 use crate::simple_preopt::do_preopt;
 use crate::unreachable_code::eliminate_unreachable_code;
 use crate::verifier::{verify_context, VerifierErrors, VerifierResult};
-use crate::{timing, CompileError};
+use crate::{timing, CodegenError, CompileError};
 #[cfg(feature = "souper-harvest")]
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -35,6 +35,65 @@ use alloc::vec::Vec;
 #[cfg(feature = "souper-harvest")]
 use crate::souper_harvest::do_souper_harvest;
 
+/// Limits on the work performed while compiling a single function.
+///
+/// Services that compile untrusted input can use these to make compilation of a pathological
+/// function fail with `CodegenError::BudgetExceeded` instead of consuming an unbounded amount of
+/// time and memory. Every limit is disabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompileBudget {
+    /// Maximum number of blocks in the function to compile.
+    pub max_blocks: Option<usize>,
+    /// Maximum number of instructions in the function to compile.
+    pub max_insts: Option<usize>,
+    /// Maximum number of values in the function to compile.
+    pub max_values: Option<usize>,
+    /// Maximum number of instructions that legalization may add to the function.
+    pub max_legalize_growth: Option<usize>,
+}
+
+impl CompileBudget {
+    /// Check that the size of `func` is within the limits of this budget.
+    pub fn check_function_size(&self, func: &Function) -> CodegenResult<()> {
+        let exceeds = |limit: Option<usize>, size: usize| matches!(limit, Some(max) if size > max);
+        if exceeds(self.max_blocks, func.dfg.num_blocks()) {
+            return Err(CodegenError::BudgetExceeded("max_blocks"));
+        }
+        if exceeds(self.max_insts, func.dfg.num_insts()) {
+            return Err(CodegenError::BudgetExceeded("max_insts"));
+        }
+        if exceeds(self.max_values, func.dfg.num_values()) {
+            return Err(CodegenError::BudgetExceeded("max_values"));
+        }
+        Ok(())
+    }
+}
+
+/// The number of entities in the IR of a function.
+///
+/// Entities that a pass removes from the layout stay allocated in the `DataFlowGraph` until the
+/// function is cleared, so they are counted too.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IrSize {
+    /// Number of instructions.
+    pub insts: usize,
+    /// Number of values.
+    pub values: usize,
+    /// Number of blocks.
+    pub blocks: usize,
+}
+
+impl IrSize {
+    /// Measure the IR of `func`.
+    pub fn of(func: &Function) -> Self {
+        Self {
+            insts: func.dfg.num_insts(),
+            values: func.dfg.num_values(),
+            blocks: func.dfg.num_blocks(),
+        }
+    }
+}
+
 /// Persistent data structures and compilation pipeline.
 pub struct Context {
     /// The function we're compiling.
@@ -54,6 +113,12 @@ pub struct Context {
 
     /// Flag: do we want a disassembly with the CompiledCode?
     pub want_disasm: bool,
+
+    /// Limits on the work performed when compiling `func`.
+    pub budget: CompileBudget,
+
+    /// Size of the IR handed to the backend by the last compilation.
+    peak_ir_size: IrSize,
 }
 
 impl Context {
@@ -77,6 +142,8 @@ impl Context {
             loop_analysis: LoopAnalysis::new(),
             compiled_code: None,
             want_disasm: false,
+            budget: CompileBudget::default(),
+            peak_ir_size: IrSize::default(),
         }
     }
 
@@ -88,6 +155,7 @@ impl Context {
         self.loop_analysis.clear();
         self.compiled_code = None;
         self.want_disasm = false;
+        self.peak_ir_size = IrSize::default();
     }
 
     /// Returns the compilation result for this function, available after any `compile` function
@@ -96,12 +164,28 @@ impl Context {
         self.compiled_code.as_ref()
     }
 
+    /// Returns the largest size the IR of the function reached during the last compilation.
+    ///
+    /// Passes only ever add entities to the IR, so this is its size once the optimizations are
+    /// done, just before lowering. It is zero until a compilation gets that far.
+    pub fn peak_ir_size(&self) -> IrSize {
+        self.peak_ir_size
+    }
+
     /// Set the flag to request a disassembly when compiling with a
     /// `MachBackend` backend.
     pub fn set_disasm(&mut self, val: bool) {
         self.want_disasm = val;
     }
 
+    /// Set the limits on the work performed by subsequent compilations.
+    ///
+    /// The budget is kept across calls to `clear`, so that it only needs to be configured once
+    /// for a context that is reused for many functions.
+    pub fn set_budget(&mut self, budget: CompileBudget) {
+        self.budget = budget;
+    }
+
     /// Compile the function, and emit machine code into a `Vec<u8>`.
     ///
     /// Run the function through all the passes necessary to generate code for the target ISA
@@ -132,6 +216,8 @@ impl Context {
     pub fn compile_stencil(&mut self, isa: &dyn TargetIsa) -> CodegenResult<CompiledCodeStencil> {
         let _tt = timing::compile();
 
+        self.peak_ir_size = IrSize::default();
+        self.budget.check_function_size(&self.func)?;
         self.verify_if(isa)?;
 
         let opt_level = isa.flags().opt_level();
@@ -170,6 +256,7 @@ impl Context {
             self.simple_gvn(isa)?;
         }
 
+        self.peak_ir_size = IrSize::of(&self.func);
         isa.compile_function(&self.func, self.want_disasm)
     }
 
@@ -277,13 +364,21 @@ impl Context {
 
     /// Run the legalizer for `isa` on the function.
     pub fn legalize(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
+        // Run some specific legalizations only.
+        let insts_before = self.func.dfg.num_insts();
+        let result = simple_legalize(&mut self.func, &mut self.cfg, &mut self.domtree, isa);
+
         // Legalization invalidates the domtree and loop_analysis by mutating the CFG.
         // TODO: Avoid doing this when legalization doesn't actually mutate the CFG.
         self.domtree.clear();
         self.loop_analysis.clear();
+        result?;
 
-        // Run some specific legalizations only.
-        simple_legalize(&mut self.func, &mut self.cfg, isa);
+        if let Some(max_growth) = self.budget.max_legalize_growth {
+            if self.func.dfg.num_insts() - insts_before > max_growth {
+                return Err(CodegenError::BudgetExceeded("max_legalize_growth"));
+            }
+        }
         self.verify_if(isa)
     }
 
@@ -356,3 +451,103 @@ impl Context {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "x86"))]
+mod tests {
+    use super::*;
+    use crate::cursor::{Cursor, FuncCursor};
+    use crate::ir::types::I64;
+    use crate::ir::{AbiParam, InstBuilder, Signature, UserFuncName};
+    use crate::isa::CallConv;
+    use crate::settings;
+
+    /// A function whose two `iadd_imm`s are each legalized into an `iconst` and an `iadd`.
+    fn two_iadd_imms() -> Function {
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(I64));
+        sig.returns.push(AbiParam::new(I64));
+        let mut func = Function::with_name_signature(UserFuncName::testcase("test"), sig);
+        let block0 = func.dfg.make_block();
+        let arg0 = func.dfg.append_block_param(block0, I64);
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(block0);
+        let v1 = pos.ins().iadd_imm(arg0, 1);
+        let v2 = pos.ins().iadd_imm(v1, 2);
+        pos.ins().return_(&[v2]);
+        func
+    }
+
+    fn compile(func: Function, budget: CompileBudget) -> CodegenResult<()> {
+        let isa = crate::isa::lookup_by_name("x86_64")
+            .unwrap()
+            .finish(settings::Flags::new(settings::builder()))
+            .unwrap();
+        let mut context = Context::for_function(func);
+        context.set_budget(budget);
+        context.compile_stencil(&*isa).map(|_| ())
+    }
+
+    #[test]
+    fn legalize_growth_budget() {
+        let budget = CompileBudget {
+            max_legalize_growth: Some(1),
+            ..CompileBudget::default()
+        };
+        assert!(matches!(
+            compile(two_iadd_imms(), budget),
+            Err(CodegenError::BudgetExceeded("max_legalize_growth"))
+        ));
+
+        let budget = CompileBudget {
+            max_legalize_growth: Some(2),
+            ..CompileBudget::default()
+        };
+        assert!(compile(two_iadd_imms(), budget).is_ok());
+    }
+
+    #[test]
+    fn function_size_budget() {
+        // The function has one block, three values and three instructions.
+        for (budget, exceeded) in [
+            (CompileBudget::default(), None),
+            (
+                CompileBudget {
+                    max_blocks: Some(1),
+                    max_insts: Some(3),
+                    max_values: Some(3),
+                    ..CompileBudget::default()
+                },
+                None,
+            ),
+            (
+                CompileBudget {
+                    max_blocks: Some(0),
+                    ..CompileBudget::default()
+                },
+                Some("max_blocks"),
+            ),
+            (
+                CompileBudget {
+                    max_insts: Some(2),
+                    ..CompileBudget::default()
+                },
+                Some("max_insts"),
+            ),
+            (
+                CompileBudget {
+                    max_values: Some(2),
+                    ..CompileBudget::default()
+                },
+                Some("max_values"),
+            ),
+        ] {
+            match (compile(two_iadd_imms(), budget), exceeded) {
+                (Ok(()), None) => {}
+                (Err(CodegenError::BudgetExceeded(limit)), Some(expected)) => {
+                    assert_eq!(limit, expected)
+                }
+                (result, _) => panic!("unexpected result for {:?}: {:?}", budget, result),
+            }
+        }
+    }
+}
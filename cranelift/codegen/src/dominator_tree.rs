@@ -695,6 +695,37 @@ mod tests {
         assert!(dtpo.dominates(block2, block2));
     }
 
+    #[test]
+    fn deep_chain() {
+        // The CFG walks use explicit stacks, so a very long chain of blocks must not
+        // overflow the host stack.
+        const NUM_BLOCKS: usize = 100_000;
+        let mut func = Function::new();
+        let blocks: Vec<Block> = (0..NUM_BLOCKS).map(|_| func.dfg.make_block()).collect();
+
+        let mut cur = FuncCursor::new(&mut func);
+        for pair in blocks.windows(2) {
+            cur.insert_block(pair[0]);
+            cur.ins().jump(pair[1], &[]);
+        }
+        cur.insert_block(blocks[NUM_BLOCKS - 1]);
+        cur.ins().return_(&[]);
+
+        let cfg = ControlFlowGraph::with_function(cur.func);
+        let dt = DominatorTree::with_function(cur.func, &cfg);
+        assert_eq!(dt.cfg_postorder().len(), NUM_BLOCKS);
+        assert_eq!(dt.cfg_postorder()[0], blocks[NUM_BLOCKS - 1]);
+        assert!(dt.dominates(blocks[0], blocks[NUM_BLOCKS - 1], &cur.func.layout));
+
+        let mut dtpo = DominatorTreePreorder::new();
+        dtpo.compute(&dt, &cur.func.layout);
+        assert!(dtpo.dominates(blocks[0], blocks[NUM_BLOCKS - 1]));
+        assert!(!dtpo.dominates(blocks[NUM_BLOCKS - 1], blocks[0]));
+
+        let flags = crate::settings::Flags::new(crate::settings::builder());
+        crate::verifier::verify_function(cur.func, &flags).unwrap();
+    }
+
     #[test]
     fn non_zero_entry_block() {
         let mut func = Function::new();
@@ -24,10 +24,11 @@
 //! and `(Block0, jmp Block2)` respectively.
 
 use crate::bforest;
-use crate::entity::SecondaryMap;
+use crate::entity::{EntitySet, SecondaryMap};
 use crate::ir::instructions::BranchInfo;
 use crate::ir::{Block, Function, Inst};
 use crate::timing;
+use alloc::vec::Vec;
 use core::mem;
 
 /// A basic block denoted by its enclosing Block and last instruction.
@@ -209,6 +210,184 @@ impl<'a> Iterator for PredIter<'a> {
 /// An iterator over block successors. The iterator type is `Block`.
 pub type SuccIter<'a> = bforest::SetIter<'a, Block>;
 
+/// An event reported by a `DfsTraversal`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraversalEvent {
+    /// The block is reached for the first time, before any of its successors.
+    Enter,
+    /// All of the block's successors have been visited.
+    Exit,
+}
+
+/// A depth-first traversal of the blocks reachable from the entry block.
+///
+/// The traversal uses an explicit stack, so it handles arbitrarily deep control flow, and the
+/// allocations are reused across traversals. Successors are visited in `succ_iter()` order.
+#[derive(Clone)]
+pub struct DfsTraversal {
+    stack: Vec<(TraversalEvent, Block)>,
+    seen: EntitySet<Block>,
+}
+
+impl DfsTraversal {
+    /// Allocate a new traversal.
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            seen: EntitySet::new(),
+        }
+    }
+
+    /// Traverse `func`, calling `visit` with `TraversalEvent::Enter` for each block in pre-order
+    /// and with `TraversalEvent::Exit` for each block in post-order.
+    pub fn traverse(
+        &mut self,
+        func: &Function,
+        cfg: &ControlFlowGraph,
+        visit: impl FnMut(TraversalEvent, Block),
+    ) {
+        match func.layout.entry_block() {
+            Some(entry) => self.traverse_from(cfg, entry, |_| true, visit),
+            None => {
+                self.stack.clear();
+                self.seen.clear();
+            }
+        }
+    }
+
+    /// Traverse the blocks reachable from `root`, only following edges to the successors for
+    /// which `follow` returns true, and calling `visit` like `traverse()` does.
+    pub fn traverse_from(
+        &mut self,
+        cfg: &ControlFlowGraph,
+        root: Block,
+        mut follow: impl FnMut(Block) -> bool,
+        mut visit: impl FnMut(TraversalEvent, Block),
+    ) {
+        debug_assert!(cfg.is_valid());
+        self.stack.clear();
+        self.seen.clear();
+        self.stack.push((TraversalEvent::Enter, root));
+
+        while let Some((event, block)) = self.stack.pop() {
+            match event {
+                TraversalEvent::Enter => {
+                    if !self.seen.insert(block) {
+                        continue;
+                    }
+                    visit(TraversalEvent::Enter, block);
+                    self.stack.push((TraversalEvent::Exit, block));
+
+                    // Push the successors in reverse so they are entered in `succ_iter()` order.
+                    let first = self.stack.len();
+                    let seen = &self.seen;
+                    self.stack.extend(
+                        cfg.succ_iter(block)
+                            .filter(|succ| !seen.contains(*succ) && follow(*succ))
+                            .map(|succ| (TraversalEvent::Enter, succ)),
+                    );
+                    self.stack[first..].reverse();
+                }
+                TraversalEvent::Exit => visit(TraversalEvent::Exit, block),
+            }
+        }
+    }
+
+    /// Compute the pre-order of the blocks reachable from the entry block.
+    pub fn preorder(&mut self, func: &Function, cfg: &ControlFlowGraph) -> Vec<Block> {
+        let mut order = Vec::new();
+        self.traverse(func, cfg, |event, block| {
+            if event == TraversalEvent::Enter {
+                order.push(block);
+            }
+        });
+        order
+    }
+
+    /// Compute the post-order of the blocks reachable from the entry block.
+    pub fn postorder(&mut self, func: &Function, cfg: &ControlFlowGraph) -> Vec<Block> {
+        let mut order = Vec::new();
+        self.traverse(func, cfg, |event, block| {
+            if event == TraversalEvent::Exit {
+                order.push(block);
+            }
+        });
+        order
+    }
+}
+
+impl Default for DfsTraversal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cached reverse post-order of the blocks reachable from the entry block.
+///
+/// Like the `ControlFlowGraph` it is computed from, this must be recomputed after the CFG
+/// changes.
+#[derive(Clone)]
+pub struct ReversePostorder {
+    order: Vec<Block>,
+    traversal: DfsTraversal,
+    valid: bool,
+}
+
+impl ReversePostorder {
+    /// Allocate a new blank reverse post-order.
+    pub fn new() -> Self {
+        Self {
+            order: Vec::new(),
+            traversal: DfsTraversal::new(),
+            valid: false,
+        }
+    }
+
+    /// Allocate and compute the reverse post-order for `func`.
+    pub fn with_function(func: &Function, cfg: &ControlFlowGraph) -> Self {
+        let mut rpo = Self::new();
+        rpo.compute(func, cfg);
+        rpo
+    }
+
+    /// Compute the reverse post-order for `func`, reusing existing allocations.
+    pub fn compute(&mut self, func: &Function, cfg: &ControlFlowGraph) {
+        let order = &mut self.order;
+        order.clear();
+        self.traversal.traverse(func, cfg, |event, block| {
+            if event == TraversalEvent::Exit {
+                order.push(block);
+            }
+        });
+        order.reverse();
+        self.valid = true;
+    }
+
+    /// Clear all data structures in this reverse post-order.
+    pub fn clear(&mut self) {
+        self.order.clear();
+        self.valid = false;
+    }
+
+    /// Check if the reverse post-order is in a valid state, i.e. `compute()` has been called
+    /// since the last `clear()`.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Get the blocks in reverse post-order.
+    pub fn blocks(&self) -> &[Block] {
+        debug_assert!(self.is_valid());
+        &self.order
+    }
+}
+
+impl Default for ReversePostorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,6 +421,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn traversal_orders() {
+        let mut func = Function::new();
+        let block0 = func.dfg.make_block();
+        let cond = func.dfg.append_block_param(block0, types::I32);
+        let block1 = func.dfg.make_block();
+        let block2 = func.dfg.make_block();
+        let block3 = func.dfg.make_block();
+        let unreachable = func.dfg.make_block();
+
+        {
+            let mut cur = FuncCursor::new(&mut func);
+
+            cur.insert_block(block0);
+            cur.ins().brnz(cond, block2, &[]);
+            cur.ins().jump(block1, &[]);
+
+            cur.insert_block(block1);
+            cur.ins().jump(block3, &[]);
+
+            cur.insert_block(block2);
+            cur.ins().brnz(cond, block2, &[]);
+            cur.ins().jump(block3, &[]);
+
+            cur.insert_block(block3);
+            cur.ins().return_(&[]);
+
+            cur.insert_block(unreachable);
+            cur.ins().jump(block3, &[]);
+        }
+
+        let cfg = ControlFlowGraph::with_function(&func);
+        let mut dfs = DfsTraversal::new();
+        assert_eq!(dfs.preorder(&func, &cfg), [block0, block1, block3, block2]);
+        assert_eq!(dfs.postorder(&func, &cfg), [block3, block1, block2, block0]);
+
+        let mut events = Vec::new();
+        dfs.traverse(&func, &cfg, |event, block| events.push((event, block)));
+        assert_eq!(events.len(), 8);
+        assert_eq!(events[0], (TraversalEvent::Enter, block0));
+        assert_eq!(events[7], (TraversalEvent::Exit, block0));
+
+        let mut entered = Vec::new();
+        dfs.traverse_from(
+            &cfg,
+            unreachable,
+            |block| block != block3,
+            |event, block| {
+                if event == TraversalEvent::Enter {
+                    entered.push(block);
+                }
+            },
+        );
+        assert_eq!(entered, [unreachable]);
+
+        let mut rpo = ReversePostorder::new();
+        assert!(!rpo.is_valid());
+        rpo.compute(&func, &cfg);
+        assert!(rpo.is_valid());
+        assert_eq!(rpo.blocks(), [block0, block2, block1, block3]);
+        rpo.clear();
+        assert!(!rpo.is_valid());
+    }
+
     #[test]
     fn branches_and_jumps() {
         let mut func = Function::new();
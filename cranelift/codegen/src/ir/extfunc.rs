@@ -33,6 +33,11 @@ pub struct Signature {
 
     /// Calling convention.
     pub call_conv: CallConv,
+
+    /// If this is the signature of a call to a variadic function, such as C's `printf`, the
+    /// number of fixed parameters at the start of `params`. The remaining parameters are the
+    /// variadic arguments passed by the call.
+    pub variadic: Option<usize>,
 }
 
 impl Signature {
@@ -42,6 +47,7 @@ impl Signature {
             params: Vec::new(),
             returns: Vec::new(),
             call_conv,
+            variadic: None,
         }
     }
 
@@ -50,6 +56,7 @@ impl Signature {
         self.params.clear();
         self.returns.clear();
         self.call_conv = call_conv;
+        self.variadic = None;
     }
 
     /// Find the index of a presumed unique special-purpose parameter.
@@ -121,7 +128,18 @@ fn write_list(f: &mut fmt::Formatter, args: &[AbiParam]) -> fmt::Result {
 impl fmt::Display for Signature {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "(")?;
-        write_list(f, &self.params)?;
+        match self.variadic {
+            None => write_list(f, &self.params)?,
+            Some(fixed) => {
+                // The variadic arguments follow a `...` marker.
+                let (fixed, variadic) = self.params.split_at(fixed.min(self.params.len()));
+                write_list(f, fixed)?;
+                write!(f, "{}...", if fixed.is_empty() { "" } else { ", " })?;
+                for arg in variadic {
+                    write!(f, ", {}", arg)?;
+                }
+            }
+        }
         write!(f, ")")?;
         if !self.returns.is_empty() {
             write!(f, " -> ")?;
@@ -372,7 +390,7 @@ impl<'a> fmt::Display for DisplayableExtFuncData<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ir::types::{B8, F32, I32};
+    use crate::ir::types::{B8, F32, F64, I32, I64};
     use alloc::string::ToString;
 
     #[test]
@@ -426,5 +444,16 @@ mod tests {
         assert_eq!(sig.to_string(), "(i32, i32x4) -> f32 windows_fastcall");
         sig.returns.push(AbiParam::new(B8));
         assert_eq!(sig.to_string(), "(i32, i32x4) -> f32, b8 windows_fastcall");
+
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.variadic = Some(0);
+        assert_eq!(sig.to_string(), "(...) system_v");
+        sig.params.push(AbiParam::new(I64));
+        assert_eq!(sig.to_string(), "(..., i64) system_v");
+        sig.variadic = Some(1);
+        assert_eq!(sig.to_string(), "(i64, ...) system_v");
+        sig.params.push(AbiParam::new(F64));
+        sig.returns.push(AbiParam::new(I32));
+        assert_eq!(sig.to_string(), "(i64, ..., f64) -> i32 system_v");
     }
 }
@@ -628,7 +628,7 @@ impl ABIMachineSpec for AArch64MachineDeps {
         insts
     }
 
-    fn gen_probestack(_: u32) -> SmallInstVec<Self::I> {
+    fn gen_probestack(_: &settings::Flags, _: u32) -> SmallInstVec<Self::I> {
         // TODO: implement if we ever require stack probes on an AArch64 host
         // (unlikely unless Lucet is ported)
         smallvec![]
@@ -975,11 +975,12 @@ impl ABIMachineSpec for AArch64MachineDeps {
     }
 
     fn gen_memcpy(
+        flags: &settings::Flags,
         call_conv: isa::CallConv,
         dst: Reg,
         src: Reg,
         tmp: Writable<Reg>,
-        _tmp2: Writable<Reg>,
+        tmp2: Writable<Reg>,
         size: usize,
     ) -> SmallVec<[Self::I; 8]> {
         let mut insts = SmallVec::new();
@@ -987,30 +988,32 @@ impl ABIMachineSpec for AArch64MachineDeps {
         let arg1 = writable_xreg(1);
         let arg2 = writable_xreg(2);
         insts.extend(Inst::load_constant(tmp, size as u64).into_iter());
-        insts.push(Inst::Call {
-            info: Box::new(CallInfo {
-                dest: ExternalName::LibCall(LibCall::Memcpy),
-                uses: smallvec![
-                    CallArgPair {
-                        vreg: dst,
-                        preg: arg0.to_reg()
-                    },
-                    CallArgPair {
-                        vreg: src,
-                        preg: arg1.to_reg()
-                    },
-                    CallArgPair {
-                        vreg: tmp.to_reg(),
-                        preg: arg2.to_reg()
-                    }
-                ],
-                defs: smallvec![],
-                clobbers: Self::get_regs_clobbered_by_call(call_conv),
-                opcode: Opcode::Call,
-                caller_callconv: call_conv,
-                callee_callconv: call_conv,
-            }),
-        });
+        insts.extend(Self::gen_call(
+            &CallDest::ExtName(
+                ExternalName::LibCall(LibCall::Memcpy),
+                abi_libcall_distance(flags),
+            ),
+            smallvec![
+                CallArgPair {
+                    vreg: dst,
+                    preg: arg0.to_reg()
+                },
+                CallArgPair {
+                    vreg: src,
+                    preg: arg1.to_reg()
+                },
+                CallArgPair {
+                    vreg: tmp.to_reg(),
+                    preg: arg2.to_reg()
+                }
+            ],
+            smallvec![],
+            Self::get_regs_clobbered_by_call(call_conv),
+            Opcode::Call,
+            tmp2,
+            call_conv,
+            call_conv,
+        ));
         insts
     }
 
@@ -1039,6 +1042,7 @@ impl ABIMachineSpec for AArch64MachineDeps {
 
     fn get_ext_mode(
         _call_conv: isa::CallConv,
+        _ty: Type,
         _specified: ir::ArgumentExtension,
     ) -> ir::ArgumentExtension {
         ir::ArgumentExtension::None
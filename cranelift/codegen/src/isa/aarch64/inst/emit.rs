@@ -98,17 +98,27 @@ pub fn mem_finalize(
 //=============================================================================
 // Instructions and subcomponents: emission
 
+/// Checks that a register's hardware encoding fits in a 5-bit field. The stack pointer is the
+/// exception: it's numbered 63 to keep it apart from the zero register and encodes as 31.
+fn assert_enc_fits(m: Reg) {
+    let enc = m.to_real_reg().unwrap().hw_enc();
+    assert_reg_field_fits!(enc < 32 || (enc == 63 && m.class() == RegClass::Int), m);
+}
+
 pub(crate) fn machreg_to_gpr(m: Reg) -> u32 {
     assert_eq!(m.class(), RegClass::Int);
+    assert_enc_fits(m);
     u32::try_from(m.to_real_reg().unwrap().hw_enc() & 31).unwrap()
 }
 
 pub(crate) fn machreg_to_vec(m: Reg) -> u32 {
     assert_eq!(m.class(), RegClass::Float);
+    assert_enc_fits(m);
     u32::try_from(m.to_real_reg().unwrap().hw_enc()).unwrap()
 }
 
 fn machreg_to_gpr_or_vec(m: Reg) -> u32 {
+    assert_enc_fits(m);
     u32::try_from(m.to_real_reg().unwrap().hw_enc() & 31).unwrap()
 }
 
@@ -934,6 +944,9 @@ impl MachInstEmit for Inst {
                     BitOp::RBit => (0b00000, 0b000000),
                     BitOp::Clz => (0b00000, 0b000100),
                     BitOp::Cls => (0b00000, 0b000101),
+                    BitOp::Rev16 => (0b00000, 0b000001),
+                    BitOp::Rev32 => (0b00000, 0b000010),
+                    BitOp::Rev64 => (0b00000, 0b000011),
                 };
                 sink.put4(enc_bit_rr(size.sf_bit(), op1, op2, rn, rd))
             }
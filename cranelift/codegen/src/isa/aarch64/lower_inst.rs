@@ -20,23 +20,13 @@ pub(crate) fn lower_insn_to_regs(
 ) -> CodegenResult<()> {
     let op = ctx.data(insn).opcode();
     let outputs = insn_outputs(ctx, insn);
-    let ty = if outputs.len() > 0 {
-        Some(ctx.output_ty(insn, 0))
-    } else {
-        None
-    };
 
     if let Ok(()) = super::lower::isle::lower(ctx, triple, flags, isa_flags, &outputs, insn) {
         return Ok(());
     }
 
-    let implemented_in_isle = |ctx: &mut Lower<Inst>| -> ! {
-        unreachable!(
-            "implemented in ISLE: inst = `{}`, type = `{:?}`",
-            ctx.dfg().display_inst(insn),
-            ty
-        );
-    };
+    let implemented_in_isle =
+        |ctx: &mut Lower<Inst>| -> CodegenResult<()> { Err(ctx.no_lowering(insn)) };
 
     match op {
         Opcode::Iconst | Opcode::Bconst | Opcode::Null => implemented_in_isle(ctx),
@@ -79,7 +69,9 @@ pub(crate) fn lower_insn_to_regs(
 
         Opcode::Rotr | Opcode::Rotl => implemented_in_isle(ctx),
 
-        Opcode::Bitrev | Opcode::Clz | Opcode::Cls | Opcode::Ctz => implemented_in_isle(ctx),
+        Opcode::Bitrev | Opcode::Bswap | Opcode::Clz | Opcode::Cls | Opcode::Ctz => {
+            implemented_in_isle(ctx)
+        }
 
         Opcode::Popcnt => implemented_in_isle(ctx),
 
@@ -132,6 +124,7 @@ pub(crate) fn lower_insn_to_regs(
 
         Opcode::Nop => {
             // Nothing.
+            Ok(())
         }
 
         Opcode::Select => implemented_in_isle(ctx),
@@ -315,6 +308,4 @@ pub(crate) fn lower_insn_to_regs(
             )));
         }
     }
-
-    Ok(())
 }
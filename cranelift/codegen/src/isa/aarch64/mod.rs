@@ -5,7 +5,7 @@ use crate::ir::{Function, Type};
 use crate::isa::aarch64::settings as aarch64_settings;
 #[cfg(feature = "unwind")]
 use crate::isa::unwind::systemv;
-use crate::isa::{Builder as IsaBuilder, TargetIsa};
+use crate::isa::{Builder as IsaBuilder, CallConv, Capabilities, TargetIsa};
 use crate::machinst::{
     compile, CompiledCode, CompiledCodeStencil, MachTextSectionBuilder, Reg, SigSet,
     TextSectionBuilder, VCode,
@@ -75,7 +75,12 @@ impl TargetIsa for AArch64Backend {
         let flags = self.flags();
         let (vcode, regalloc_result) = self.compile_vcode(func, flags.clone())?;
 
-        let emit_result = vcode.emit(&regalloc_result, want_disasm, flags.machine_code_cfg_info());
+        let emit_result = vcode.emit(
+            &regalloc_result,
+            want_disasm,
+            flags.machine_code_cfg_info(),
+            flags.align_loops(),
+        );
         let frame_size = emit_result.frame_size;
         let value_labels_ranges = emit_result.value_labels_ranges;
         let buffer = emit_result.buffer.finish();
@@ -123,6 +128,24 @@ impl TargetIsa for AArch64Backend {
         16
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            i128: true,
+            simd: self.flags.enable_simd(),
+            dynamic_vectors: true,
+            // Without LSE, atomic operations are lowered to exclusive load/store loops.
+            atomics: true,
+            call_conventions: &[
+                CallConv::Fast,
+                CallConv::Cold,
+                CallConv::SystemV,
+                CallConv::AppleAarch64,
+                CallConv::WasmtimeSystemV,
+                CallConv::WasmtimeAppleAarch64,
+            ],
+        }
+    }
+
     fn unsigned_add_overflow_condition(&self) -> IntCC {
         // Unsigned `>=`; this corresponds to the carry flag set on aarch64, which happens on
         // overflow of an add.
@@ -410,4 +433,44 @@ mod test {
 
         assert_eq!(code, &golden[..]);
     }
+
+    #[test]
+    fn test_no_lowering() {
+        let name = UserFuncName::testcase("test0");
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(I128));
+        sig.params.push(AbiParam::new(I128));
+        sig.returns.push(AbiParam::new(I128));
+        let mut func = Function::with_name_signature(name, sig);
+
+        let bb0 = func.dfg.make_block();
+        let arg0 = func.dfg.append_block_param(bb0, I128);
+        let arg1 = func.dfg.append_block_param(bb0, I128);
+
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(bb0);
+        let v0 = pos.ins().udiv(arg0, arg1);
+        pos.ins().return_(&[v0]);
+
+        let shared_flags = settings::Flags::new(settings::builder());
+        let isa_flags = aarch64_settings::Flags::new(&shared_flags, aarch64_settings::builder());
+        let backend = AArch64Backend::new_with_flags(
+            Triple::from_str("aarch64").unwrap(),
+            shared_flags,
+            isa_flags,
+        );
+        match backend.compile_function(&func, false) {
+            Err(crate::CodegenError::NoLowering {
+                inst,
+                opcode,
+                ctrl_type,
+            }) => {
+                assert_eq!(inst, "v2 = udiv.i128 v0, v1");
+                assert_eq!(opcode, crate::ir::Opcode::Udiv);
+                assert_eq!(ctrl_type, I128);
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("i128 udiv should not be lowered"),
+        }
+    }
 }
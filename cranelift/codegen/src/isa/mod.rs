@@ -214,6 +214,24 @@ impl TargetFrontendConfig {
     }
 }
 
+/// A summary of what a target ISA supports with the flags it was built with.
+///
+/// Embedders can use this to choose a backend, or to decide which features to polyfill, before
+/// compiling any code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether the 128-bit integer type `i128` is supported.
+    pub i128: bool,
+    /// Whether 128-bit SIMD vector types and operations are supported.
+    pub simd: bool,
+    /// Whether dynamic vector types are supported.
+    pub dynamic_vectors: bool,
+    /// Whether the atomic memory instructions are supported.
+    pub atomics: bool,
+    /// The calling conventions that functions can be compiled with and can call.
+    pub call_conventions: &'static [CallConv],
+}
+
 /// Methods that are specialized to a target ISA.
 ///
 /// Implies a Display trait that shows the shared flags, as well as any ISA-specific flags.
@@ -235,9 +253,18 @@ pub trait TargetIsa: fmt::Display + Send + Sync {
         false
     }
 
+    /// Whether the backend lowers scalar carry and borrow arithmetic (`iadd_cout`, `isub_borrow`
+    /// and friends) itself. Otherwise the legalizer expands it into comparisons.
+    fn lowers_carry_arithmetic(&self) -> bool {
+        false
+    }
+
     /// Get the ISA-dependent maximum vector register size, in bytes.
     fn dynamic_vector_bytes(&self, dynamic_ty: ir::Type) -> u32;
 
+    /// Report which types, instructions and calling conventions are supported.
+    fn capabilities(&self) -> Capabilities;
+
     /// Compile the given function.
     fn compile_function(
         &self,
@@ -223,9 +223,16 @@ impl ABIMachineSpec for Riscv64MachineDeps {
 
     fn get_ext_mode(
         _call_conv: isa::CallConv,
+        ty: Type,
         specified: ir::ArgumentExtension,
     ) -> ir::ArgumentExtension {
-        specified
+        // The LP64 psABI keeps 32-bit integers sign-extended to 64 bits in registers, even
+        // unsigned ones.
+        if ty == I32 && specified != ir::ArgumentExtension::None {
+            ir::ArgumentExtension::Sext
+        } else {
+            specified
+        }
     }
 
     fn gen_args(_isa_flags: &crate::isa::riscv64::settings::Flags, args: Vec<ArgPair>) -> Inst {
@@ -309,6 +316,10 @@ impl ABIMachineSpec for Riscv64MachineDeps {
         }
     }
 
+    fn uses_outgoing_args_area() -> bool {
+        true
+    }
+
     fn gen_prologue_frame_setup(flags: &settings::Flags) -> SmallInstVec<Inst> {
         // add  sp,sp,-16    ;; alloc stack space for fp.
         // sd   ra,8(sp)     ;; save ra.
@@ -357,40 +368,43 @@ impl ABIMachineSpec for Riscv64MachineDeps {
         insts
     }
 
-    fn gen_probestack(frame_size: u32) -> SmallInstVec<Self::I> {
+    fn gen_probestack(flags: &settings::Flags, frame_size: u32) -> SmallInstVec<Self::I> {
         let mut insts = SmallVec::new();
         insts.extend(Inst::load_constant_u32(writable_a0(), frame_size as u64));
-        insts.push(Inst::Call {
-            info: Box::new(CallInfo {
-                dest: ExternalName::LibCall(LibCall::Probestack),
-                uses: smallvec![CallArgPair {
-                    vreg: a0(),
-                    preg: a0(),
-                }],
-                defs: smallvec![],
-                clobbers: PRegSet::empty(),
-                opcode: Opcode::Call,
-                callee_callconv: CallConv::SystemV,
-                caller_callconv: CallConv::SystemV,
-            }),
-        });
+        insts.extend(Self::gen_call(
+            &CallDest::ExtName(
+                ExternalName::LibCall(LibCall::Probestack),
+                abi_libcall_distance(flags),
+            ),
+            smallvec![CallArgPair {
+                vreg: a0(),
+                preg: a0(),
+            }],
+            smallvec![],
+            PRegSet::empty(),
+            Opcode::Call,
+            writable_spilltmp_reg(),
+            CallConv::SystemV,
+            CallConv::SystemV,
+        ));
         insts
     }
-    // Returns stack bytes used as well as instructions. Does not adjust
-    // nominal SP offset; abi_impl generic code will do that.
+    // Returns stack bytes used as well as instructions. Only adjusts the
+    // nominal SP offset for the outgoing-argument area, which sits below it;
+    // abi_impl generic code takes care of the rest.
     fn gen_clobber_save(
         _call_conv: isa::CallConv,
         setup_frame: bool,
         flags: &settings::Flags,
         clobbered_callee_saves: &[Writable<RealReg>],
         fixed_frame_storage_size: u32,
-        _outgoing_args_size: u32,
+        outgoing_args_size: u32,
     ) -> (u64, SmallVec<[Inst; 16]>) {
         let mut insts = SmallVec::new();
         let clobbered_size = compute_clobber_size(&clobbered_callee_saves);
-        // Adjust the stack pointer downward for clobbers and the function fixed
-        // frame (spillslots and storage slots).
-        let stack_size = fixed_frame_storage_size + clobbered_size;
+        // Adjust the stack pointer downward for clobbers, the function fixed
+        // frame (spillslots and storage slots) and the outgoing arguments.
+        let stack_size = fixed_frame_storage_size + clobbered_size + outgoing_args_size;
         if flags.unwind_info() && setup_frame {
             // The *unwind* frame (but not the actual frame) starts at the
             // clobbers, just below the saved FP/LR pair.
@@ -431,6 +445,9 @@ impl ABIMachineSpec for Riscv64MachineDeps {
                 amount: -(stack_size as i64),
             });
         }
+        if outgoing_args_size > 0 {
+            insts.push(Self::gen_nominal_sp_adj(outgoing_args_size as i32));
+        }
         (clobbered_size as u64, insts)
     }
 
@@ -440,12 +457,14 @@ impl ABIMachineSpec for Riscv64MachineDeps {
         _flags: &settings::Flags,
         clobbers: &[Writable<RealReg>],
         fixed_frame_storage_size: u32,
-        _outgoing_args_size: u32,
+        outgoing_args_size: u32,
     ) -> SmallVec<[Inst; 16]> {
         let mut insts = SmallVec::new();
         let clobbered_callee_saves =
             Self::get_clobbered_callee_saves(call_conv, _flags, sig, clobbers);
-        let stack_size = fixed_frame_storage_size + compute_clobber_size(&clobbered_callee_saves);
+        let stack_size = fixed_frame_storage_size
+            + compute_clobber_size(&clobbered_callee_saves)
+            + outgoing_args_size;
         if stack_size > 0 {
             insts.push(Inst::AjustSp {
                 amount: stack_size as i64,
@@ -525,11 +544,12 @@ impl ABIMachineSpec for Riscv64MachineDeps {
     }
 
     fn gen_memcpy(
+        flags: &settings::Flags,
         call_conv: isa::CallConv,
         dst: Reg,
         src: Reg,
         tmp: Writable<Reg>,
-        _tmp2: Writable<Reg>,
+        tmp2: Writable<Reg>,
         size: usize,
     ) -> SmallVec<[Self::I; 8]> {
         let mut insts = SmallVec::new();
@@ -537,30 +557,32 @@ impl ABIMachineSpec for Riscv64MachineDeps {
         let arg1 = Writable::from_reg(x_reg(11));
         let arg2 = Writable::from_reg(x_reg(12));
         insts.extend(Inst::load_constant_u64(tmp, size as u64).into_iter());
-        insts.push(Inst::Call {
-            info: Box::new(CallInfo {
-                dest: ExternalName::LibCall(LibCall::Memcpy),
-                uses: smallvec![
-                    CallArgPair {
-                        vreg: dst,
-                        preg: arg0.to_reg()
-                    },
-                    CallArgPair {
-                        vreg: src,
-                        preg: arg1.to_reg()
-                    },
-                    CallArgPair {
-                        vreg: tmp.to_reg(),
-                        preg: arg2.to_reg()
-                    }
-                ],
-                defs: smallvec![],
-                clobbers: Self::get_regs_clobbered_by_call(call_conv),
-                opcode: Opcode::Call,
-                caller_callconv: call_conv,
-                callee_callconv: call_conv,
-            }),
-        });
+        insts.extend(Self::gen_call(
+            &CallDest::ExtName(
+                ExternalName::LibCall(LibCall::Memcpy),
+                abi_libcall_distance(flags),
+            ),
+            smallvec![
+                CallArgPair {
+                    vreg: dst,
+                    preg: arg0.to_reg()
+                },
+                CallArgPair {
+                    vreg: src,
+                    preg: arg1.to_reg()
+                },
+                CallArgPair {
+                    vreg: tmp.to_reg(),
+                    preg: arg2.to_reg()
+                }
+            ],
+            smallvec![],
+            Self::get_regs_clobbered_by_call(call_conv),
+            Opcode::Call,
+            tmp2,
+            call_conv,
+            call_conv,
+        ));
         insts
     }
 
@@ -90,7 +90,9 @@ impl LoadConstant {
 }
 
 pub(crate) fn reg_to_gpr_num(m: Reg) -> u32 {
-    u32::try_from(m.to_real_reg().unwrap().hw_enc() & 31).unwrap()
+    let enc = m.to_real_reg().unwrap().hw_enc();
+    assert_reg_field_fits!(enc < 32, m);
+    u32::from(enc)
 }
 
 /// State carried between emissions of a sequence of instructions.
@@ -1247,6 +1249,33 @@ impl MachInstEmit for Inst {
                 sink.put4(x);
             }
 
+            &Inst::LoadConstPool { rd, tmp, ty, bits } => {
+                let rd = allocs.next_writable(rd);
+                let tmp = allocs.next_writable(tmp);
+                let size = ty.bytes();
+                let label = sink.get_label();
+                sink.defer_constant(
+                    label,
+                    size,
+                    &bits.to_le_bytes()[..size as usize],
+                    LabelUse::PCRel32.max_pos_range(),
+                );
+                // auipc + load is patched the same way as auipc + jalr.
+                sink.use_label_at_offset(sink.cur_offset(), label, LabelUse::PCRel32);
+                Inst::Auipc {
+                    rd: tmp,
+                    imm: Imm20::from_bits(0),
+                }
+                .emit(&[], sink, emit_info, state);
+                Inst::Load {
+                    rd,
+                    op: LoadOP::from_type(ty),
+                    flags: MemFlags::trusted(),
+                    from: AMode::RegOffset(tmp.to_reg(), 0, ty),
+                }
+                .emit(&[], sink, emit_info, state);
+            }
+
             &Inst::LoadAddr { rd, mem } => {
                 let base = mem.get_base_register();
                 let base = allocs.next(base);
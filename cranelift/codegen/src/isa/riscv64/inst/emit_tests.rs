@@ -2200,6 +2200,32 @@ fn xxx() {
     x.print_b();
 }
 
+#[test]
+fn riscv64_load_const_pool() {
+    let (flags, isa_flags) = make_test_flags();
+    let emit_info = EmitInfo::new(flags, isa_flags);
+    let inst = Inst::LoadConstPool {
+        rd: writable_fa0(),
+        tmp: writable_spilltmp_reg(),
+        ty: F64,
+        bits: 0x400921fb54442d18,
+    };
+    assert_eq!(
+        inst.print_with_state(&mut EmitState::default(), &mut AllocationConsumer::new(&[])),
+        "fld fa0,const(0x400921fb54442d18)##tmp=t6"
+    );
+    let mut buffer = MachBuffer::new();
+    inst.emit(&[], &mut buffer, &emit_info, &mut Default::default());
+    let buffer = buffer.finish();
+    let mut expected = vec![];
+    // auipc t6,0
+    expected.extend_from_slice(&0x00000f97u32.to_le_bytes());
+    // fld fa0,8(t6)
+    expected.extend_from_slice(&0x008fb507u32.to_le_bytes());
+    expected.extend_from_slice(&0x400921fb54442d18u64.to_le_bytes());
+    assert_eq!(buffer.data(), &expected[..]);
+}
+
 #[test]
 fn riscv64_worst_case_instruction_size() {
     let (flags, isa_flags) = make_test_flags();
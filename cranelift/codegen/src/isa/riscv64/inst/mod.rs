@@ -20,7 +20,7 @@ pub use crate::ir::condcodes::FloatCC;
 
 use alloc::vec::Vec;
 use regalloc2::{PRegSet, VReg};
-use smallvec::SmallVec;
+use smallvec::{smallvec, SmallVec};
 use std::boxed::Box;
 use std::string::{String, ToString};
 
@@ -262,15 +262,7 @@ impl Inst {
         const_data: u32,
         tmp: Writable<Reg>,
     ) -> SmallVec<[Inst; 4]> {
-        let mut insts = SmallVec::new();
-        insts.extend(Self::load_constant_u32(tmp, const_data as u64));
-        insts.push(Inst::FpuRR {
-            frm: None,
-            alu_op: FpuOPRR::move_x_to_f_op(F32),
-            rd,
-            rs: tmp.to_reg(),
-        });
-        insts
+        Self::load_fp_constant(rd, F32, const_data as u64, tmp)
     }
 
     /// Create instructions that load a 64-bit floating-point constant.
@@ -279,15 +271,34 @@ impl Inst {
         const_data: u64,
         tmp: WritableReg,
     ) -> SmallVec<[Inst; 4]> {
-        let mut insts = SmallInstVec::new();
-        insts.extend(Self::load_constant_u64(tmp, const_data));
-        insts.push(Inst::FpuRR {
-            frm: None,
-            alu_op: FpuOPRR::move_x_to_f_op(F64),
-            rd,
-            rs: tmp.to_reg(),
-        });
-        insts
+        Self::load_fp_constant(rd, F64, const_data, tmp)
+    }
+
+    /// Bit patterns that can be built with lui/addi are materialized in
+    /// `tmp` and moved over; anything else is loaded from the constant pool.
+    fn load_fp_constant(
+        rd: Writable<Reg>,
+        ty: Type,
+        const_data: u64,
+        tmp: Writable<Reg>,
+    ) -> SmallInstVec<Inst> {
+        match Inst::load_const_imm(tmp, const_data) {
+            Some(mut insts) => {
+                insts.push(Inst::FpuRR {
+                    frm: None,
+                    alu_op: FpuOPRR::move_x_to_f_op(ty),
+                    rd,
+                    rs: tmp.to_reg(),
+                });
+                insts
+            }
+            None => smallvec![Inst::LoadConstPool {
+                rd,
+                tmp,
+                ty,
+                bits: const_data,
+            }],
+        }
     }
 
     /// Generic constructor for a load (zero-extending where appropriate).
@@ -398,6 +409,10 @@ fn riscv64_get_operands<F: Fn(VReg) -> VReg>(inst: &Inst, collector: &mut Operan
         &Inst::LoadExtName { rd, .. } => {
             collector.reg_def(rd);
         }
+        &Inst::LoadConstPool { rd, tmp, .. } => {
+            collector.reg_def(rd);
+            collector.reg_def(tmp);
+        }
         &Inst::LoadAddr { rd, mem } => {
             collector.reg_use(mem.get_base_register());
             collector.reg_early_def(rd);
@@ -730,6 +745,12 @@ impl MachInst for Inst {
         Inst::Nop4
     }
 
+    /// Simple in-order cores fetch aligned 8-byte pairs of instructions, so
+    /// a loop starting on such a boundary doesn't waste a fetch slot.
+    fn loop_header_alignment() -> CodeOffset {
+        8
+    }
+
     fn rc_for_type(ty: Type) -> CodegenResult<(&'static [RegClass], &'static [Type])> {
         match ty {
             I8 => Ok((&[RegClass::Int], &[I8])),
@@ -1509,6 +1530,12 @@ impl Inst {
                 let rd = format_reg(rd.to_reg(), allocs);
                 format!("load_sym {},{}{:+}", rd, name.display(None), offset)
             }
+            &MInst::LoadConstPool { rd, tmp, ty, bits } => {
+                let rd = format_reg(rd.to_reg(), allocs);
+                let tmp = format_reg(tmp.to_reg(), allocs);
+                let op = LoadOP::from_type(ty).op_name();
+                format!("{} {},const({:#x})##tmp={}", op, rd, bits, tmp)
+            }
             &MInst::LoadAddr { ref rd, ref mem } => {
                 let rs = mem.to_addr(allocs);
                 let rd = format_reg(rd.to_reg(), allocs);
@@ -1712,9 +1739,10 @@ impl LabelUse {
                     buffer[0..4].clone_from_slice(&u32::to_le_bytes(
                         insn | enc_auipc(writable_zero_reg(), imm20),
                     ));
-                    buffer[4..8].clone_from_slice(&u32::to_le_bytes(
-                        insn2 | enc_jalr(writable_zero_reg(), zero_reg(), imm12),
-                    ));
+                    // The second instruction is any I-type instruction
+                    // (jalr for calls and jumps, a load for constant pool
+                    // references), so only or in the immediate field.
+                    buffer[4..8].clone_from_slice(&u32::to_le_bytes(insn2 | imm12.as_u32() << 20));
                 })
                 // expect make sure we handled.
                 .expect("we have check the range before,this is a compiler error.");
@@ -1746,4 +1774,52 @@ mod test {
         assert!(LabelUse::PCRel32.max_neg_range() == (Inst::imm_min().abs() as CodeOffset));
         assert!(LabelUse::B12.max_pos_range() == ((1 << 11) - 1) * 2);
     }
+
+    /// Decode an immediate that is scattered over an instruction, given the layout of its fields
+    /// as `(instruction lsb, immediate lsb, width)`, and sign-extend it from `sign_bit`.
+    fn decode_imm(insn: u32, fields: &[(u32, u32, u32)], sign_bit: u32) -> i64 {
+        let raw = fields
+            .iter()
+            .fold(0u64, |imm, &(insn_lsb, imm_lsb, width)| {
+                imm | (((insn >> insn_lsb) & ((1 << width) - 1)) as u64) << imm_lsb
+            });
+        ((raw << (63 - sign_bit)) as i64) >> (63 - sign_bit)
+    }
+
+    #[test]
+    fn label_use_patch_all_offsets() {
+        // Immediate layouts from the "Immediate Encoding Variants" section of the RISC-V spec.
+        const B_TYPE: &[(u32, u32, u32)] = &[(31, 12, 1), (25, 5, 6), (8, 1, 4), (7, 11, 1)];
+        const J_TYPE: &[(u32, u32, u32)] = &[(31, 20, 1), (21, 1, 10), (20, 11, 1), (12, 12, 8)];
+        // bne x31, x31, 0
+        let bne = 0x63 | (0b001 << 12) | (31 << 15) | (31 << 20);
+        // jal x31, 0
+        let jal = 0x6f | (31 << 7);
+
+        for (kind, insn, fields, sign_bit) in [
+            (LabelUse::B12, bne, B_TYPE, 12),
+            (LabelUse::Jal20, jal, J_TYPE, 20),
+        ] {
+            let imm_mask = fields.iter().fold(0u32, |mask, &(insn_lsb, _, width)| {
+                mask | ((1 << width) - 1) << insn_lsb
+            });
+            assert_eq!(insn & imm_mask, 0);
+
+            let min = -(kind.max_neg_range() as i64);
+            let max = kind.max_pos_range() as i64;
+            for offset in (min..=max).step_by(2) {
+                let mut buffer = u32::to_le_bytes(insn);
+                kind.patch_raw_offset(&mut buffer, offset);
+                let patched = u32::from_le_bytes(buffer);
+                assert_eq!(patched & !imm_mask, insn, "{:?} offset {}", kind, offset);
+                assert_eq!(
+                    decode_imm(patched, fields, sign_bit),
+                    offset,
+                    "{:?} offset {}",
+                    kind,
+                    offset
+                );
+            }
+        }
+    }
 }
@@ -48,10 +48,7 @@ impl LowerBackend for Riscv64Backend {
         ) {
             return Ok(());
         }
-        unreachable!(
-            "implemented in ISLE: branch = `{}`",
-            ctx.dfg().display_inst(branches[0]),
-        );
+        Err(ctx.no_lowering(branches[0]))
     }
 
     fn maybe_pinned_reg(&self) -> Option<Reg> {
@@ -20,17 +20,8 @@ pub(crate) fn lower_insn_to_regs(
     isa_flags: &riscv64_settings::Flags,
 ) -> CodegenResult<()> {
     let outputs = insn_outputs(ctx, insn);
-    let ty = if outputs.len() > 0 {
-        Some(ctx.output_ty(insn, 0))
-    } else {
-        None
-    };
     if let Ok(()) = super::lower::isle::lower(ctx, flags, triple, isa_flags, &outputs, insn) {
         return Ok(());
     }
-    unreachable!(
-        "not implemented in ISLE: inst = `{}`, type = `{:?}`",
-        ctx.dfg().display_inst(insn),
-        ty
-    );
+    Err(ctx.no_lowering(insn))
 }
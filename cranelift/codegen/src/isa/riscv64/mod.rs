@@ -5,9 +5,9 @@ use crate::ir::condcodes::IntCC;
 use crate::ir::Function;
 
 use crate::isa::riscv64::settings as riscv_settings;
-use crate::isa::{Builder as IsaBuilder, TargetIsa};
+use crate::isa::{Builder as IsaBuilder, CallConv, Capabilities, TargetIsa};
 use crate::machinst::{
-    compile, CompiledCode, CompiledCodeStencil, MachTextSectionBuilder, Reg, SigSet,
+    compile, CompiledCode, CompiledCodeStencil, MachInst, MachTextSectionBuilder, Reg, SigSet,
     TextSectionBuilder, VCode,
 };
 use crate::result::CodegenResult;
@@ -76,7 +76,12 @@ impl TargetIsa for Riscv64Backend {
         let (vcode, regalloc_result) = self.compile_vcode(func, flags.clone())?;
 
         let want_disasm = want_disasm || log::log_enabled!(log::Level::Debug);
-        let emit_result = vcode.emit(&regalloc_result, want_disasm, flags.machine_code_cfg_info());
+        let emit_result = vcode.emit(
+            &regalloc_result,
+            want_disasm,
+            flags.machine_code_cfg_info(),
+            flags.align_loops(),
+        );
         let frame_size = emit_result.frame_size;
         let value_labels_ranges = emit_result.value_labels_ranges;
         let buffer = emit_result.buffer.finish();
@@ -107,6 +112,26 @@ impl TargetIsa for Riscv64Backend {
         16
     }
 
+    fn lowers_carry_arithmetic(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            i128: true,
+            simd: false,
+            dynamic_vectors: false,
+            // Atomic operations are lowered to instructions from the A extension.
+            atomics: self.isa_flags.has_a(),
+            call_conventions: &[
+                CallConv::Fast,
+                CallConv::Cold,
+                CallConv::SystemV,
+                CallConv::WasmtimeSystemV,
+            ],
+        }
+    }
+
     fn triple(&self) -> &Triple {
         &self.triple
     }
@@ -161,8 +186,14 @@ impl TargetIsa for Riscv64Backend {
         inst::unwind::systemv::map_reg(reg).map(|reg| reg.0)
     }
 
+    /// Loop headers are only aligned relative to the start of the function, so with
+    /// `align_loops` the function itself must be at least as aligned as they are.
     fn function_alignment(&self) -> u32 {
-        4
+        if self.flags.align_loops() {
+            inst::Inst::loop_header_alignment().max(4)
+        } else {
+            4
+        }
     }
 }
 
@@ -243,4 +274,15 @@ mod test {
         ];
         assert_eq!(code, &golden[..]);
     }
+
+    #[test]
+    fn test_function_alignment_covers_loop_headers() {
+        let mut shared_flags = settings::builder();
+        shared_flags.enable("align_loops").unwrap();
+        let isa = crate::isa::lookup_by_name("riscv64")
+            .unwrap()
+            .finish(settings::Flags::new(shared_flags))
+            .unwrap();
+        assert!(isa.function_alignment() >= inst::Inst::loop_header_alignment());
+    }
 }
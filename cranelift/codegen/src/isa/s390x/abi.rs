@@ -575,7 +575,7 @@ impl ABIMachineSpec for S390xMachineDeps {
         SmallVec::new()
     }
 
-    fn gen_probestack(_: u32) -> SmallInstVec<Self::I> {
+    fn gen_probestack(_: &settings::Flags, _: u32) -> SmallInstVec<Self::I> {
         // TODO: implement if we ever require stack probes on an s390x host
         // (unlikely unless Lucet is ported)
         smallvec![]
@@ -755,6 +755,7 @@ impl ABIMachineSpec for S390xMachineDeps {
     }
 
     fn gen_memcpy(
+        _flags: &settings::Flags,
         _call_conv: isa::CallConv,
         _dst: Reg,
         _src: Reg,
@@ -789,6 +790,7 @@ impl ABIMachineSpec for S390xMachineDeps {
 
     fn get_ext_mode(
         _call_conv: isa::CallConv,
+        _ty: Type,
         specified: ir::ArgumentExtension,
     ) -> ir::ArgumentExtension {
         specified
@@ -1,6 +1,6 @@
 //! S390x ISA: binary code emission.
 
-use crate::binemit::{Reloc, StackMap};
+use crate::binemit::{CodeOffset, Reloc, StackMap};
 use crate::ir::{MemFlags, RelSourceLoc, TrapCode};
 use crate::isa::s390x::abi::S390xMachineDeps;
 use crate::isa::s390x::inst::*;
@@ -411,12 +411,16 @@ pub fn mem_vrx_emit(
 
 fn machreg_to_gpr(m: Reg) -> u8 {
     assert_eq!(m.class(), RegClass::Int);
-    u8::try_from(m.to_real_reg().unwrap().hw_enc()).unwrap()
+    let reg = m.to_real_reg().unwrap().hw_enc();
+    assert_reg_field_fits!(reg < 16, m);
+    reg
 }
 
 fn machreg_to_vr(m: Reg) -> u8 {
     assert_eq!(m.class(), RegClass::Float);
-    u8::try_from(m.to_real_reg().unwrap().hw_enc()).unwrap()
+    let reg = m.to_real_reg().unwrap().hw_enc();
+    assert_reg_field_fits!(reg < 32, m);
+    reg
 }
 
 fn machreg_to_fpr(m: Reg) -> u8 {
@@ -1286,14 +1290,14 @@ fn put_with_reloc(
     ri2_name: &ExternalName,
     ri2_offset: i64,
 ) {
-    let len = enc.len();
-    for i in 0..offset {
-        sink.put1(enc[i]);
-    }
-    sink.add_reloc(ri2_reloc, ri2_name, ri2_offset + offset as i64);
-    for i in offset..len {
-        sink.put1(enc[i]);
-    }
+    let reloc_offset = sink.cur_offset() + offset as CodeOffset;
+    sink.add_reloc_at_offset(
+        reloc_offset,
+        ri2_reloc,
+        ri2_name,
+        ri2_offset + offset as i64,
+    );
+    sink.put_data(enc);
 }
 
 /// State carried between emissions of a sequence of instructions.
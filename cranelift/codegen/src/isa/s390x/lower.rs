@@ -24,12 +24,6 @@ impl LowerBackend for S390xBackend {
                 output: i,
             })
             .collect();
-        let ty = if outputs.len() > 0 {
-            Some(ctx.output_ty(ir_inst, 0))
-        } else {
-            None
-        };
-
         if let Ok(()) = super::lower::isle::lower(
             ctx,
             &self.triple,
@@ -105,6 +99,7 @@ impl LowerBackend for S390xBackend {
             | Opcode::Bmask
             | Opcode::Bint
             | Opcode::Bitrev
+            | Opcode::Bswap
             | Opcode::Clz
             | Opcode::Cls
             | Opcode::Ctz
@@ -192,13 +187,7 @@ impl LowerBackend for S390xBackend {
             | Opcode::TlsValue
             | Opcode::GetFramePointer
             | Opcode::GetStackPointer
-            | Opcode::GetReturnAddress => {
-                unreachable!(
-                    "implemented in ISLE: inst = `{}`, type = `{:?}`",
-                    ctx.dfg().display_inst(ir_inst),
-                    ty
-                )
-            }
+            | Opcode::GetReturnAddress => Err(ctx.no_lowering(ir_inst)),
 
             Opcode::GetPinnedReg
             | Opcode::SetPinnedReg
@@ -207,13 +196,7 @@ impl LowerBackend for S390xBackend {
             | Opcode::DynamicStackLoad
             | Opcode::DynamicStackStore
             | Opcode::DynamicStackAddr
-            | Opcode::ExtractVector => {
-                unreachable!(
-                    "TODO: not yet implemented in ISLE: inst = `{}`, type = `{:?}`",
-                    ctx.dfg().display_inst(ir_inst),
-                    ty
-                )
-            }
+            | Opcode::ExtractVector => Err(ctx.no_lowering(ir_inst)),
 
             Opcode::StackLoad | Opcode::StackStore => {
                 panic!("Direct stack memory access not supported; should not be used by Wasm");
@@ -307,9 +290,6 @@ impl LowerBackend for S390xBackend {
         ) {
             return Ok(());
         }
-        unreachable!(
-            "implemented in ISLE: branch = `{}`",
-            ctx.dfg().display_inst(branches[0]),
-        );
+        Err(ctx.no_lowering(branches[0]))
     }
 }
@@ -5,7 +5,7 @@ use crate::ir::{Function, Type};
 use crate::isa::s390x::settings as s390x_settings;
 #[cfg(feature = "unwind")]
 use crate::isa::unwind::systemv::RegisterMappingError;
-use crate::isa::{Builder as IsaBuilder, TargetIsa};
+use crate::isa::{Builder as IsaBuilder, CallConv, Capabilities, TargetIsa};
 use crate::machinst::{
     compile, CompiledCode, CompiledCodeStencil, MachTextSectionBuilder, Reg, SigSet,
     TextSectionBuilder, VCode,
@@ -73,7 +73,12 @@ impl TargetIsa for S390xBackend {
         let flags = self.flags();
         let (vcode, regalloc_result) = self.compile_vcode(func)?;
 
-        let emit_result = vcode.emit(&regalloc_result, want_disasm, flags.machine_code_cfg_info());
+        let emit_result = vcode.emit(
+            &regalloc_result,
+            want_disasm,
+            flags.machine_code_cfg_info(),
+            flags.align_loops(),
+        );
         let frame_size = emit_result.frame_size;
         let value_labels_ranges = emit_result.value_labels_ranges;
         let buffer = emit_result.buffer.finish();
@@ -117,6 +122,21 @@ impl TargetIsa for S390xBackend {
         16
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            i128: true,
+            simd: self.flags.enable_simd(),
+            dynamic_vectors: false,
+            atomics: true,
+            call_conventions: &[
+                CallConv::Fast,
+                CallConv::Cold,
+                CallConv::SystemV,
+                CallConv::WasmtimeSystemV,
+            ],
+        }
+    }
+
     fn unsigned_add_overflow_condition(&self) -> IntCC {
         // The ADD LOGICAL family of instructions set the condition code
         // differently from normal comparisons, in a way that cannot be
@@ -11,7 +11,7 @@ use crate::{CodegenError, CodegenResult};
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use args::*;
-use regalloc2::{PRegSet, VReg};
+use regalloc2::{PReg, PRegSet, VReg};
 use smallvec::{smallvec, SmallVec};
 use std::convert::TryFrom;
 
@@ -252,6 +252,29 @@ impl ABIMachineSpec for X64ABIMachineSpec {
         Ok((ret, next_stack as i64, extra_arg))
     }
 
+    fn variadic_call_arg(
+        call_conv: isa::CallConv,
+        args: &[ABIArg],
+    ) -> CodegenResult<Option<(PReg, u64)>> {
+        if call_conv != CallConv::SystemV {
+            return Err(CodegenError::Unsupported(format!(
+                "variadic calls with the {} calling convention",
+                call_conv
+            )));
+        }
+        // Variadic arguments are classified like fixed ones. The callee's prologue needs an
+        // upper bound on the number of vector registers used, which is passed in %al.
+        let vector_regs = args
+            .iter()
+            .flat_map(|arg| match arg {
+                ABIArg::Slots { slots, .. } => &slots[..],
+                _ => &[],
+            })
+            .filter(|slot| matches!(slot, ABIArgSlot::Reg { reg, .. } if reg.class() == RegClass::Float))
+            .count();
+        Ok(Some((regs::gpr_preg(regs::ENC_RAX), vector_regs as u64)))
+    }
+
     fn fp_to_arg_offset(_call_conv: isa::CallConv, _flags: &settings::Flags) -> i64 {
         16 // frame pointer + return address.
     }
@@ -419,24 +442,30 @@ impl ABIMachineSpec for X64ABIMachineSpec {
         insts
     }
 
-    fn gen_probestack(frame_size: u32) -> SmallInstVec<Self::I> {
+    fn gen_probestack(flags: &settings::Flags, frame_size: u32) -> SmallInstVec<Self::I> {
         let mut insts = SmallVec::new();
         insts.push(Inst::imm(
             OperandSize::Size32,
             frame_size as u64,
             Writable::from_reg(regs::rax()),
         ));
-        insts.push(Inst::CallKnown {
-            dest: ExternalName::LibCall(LibCall::Probestack),
-            info: Box::new(CallInfo {
-                // No need to include arg here: we are post-regalloc
-                // so no constraints will be seen anyway.
-                uses: smallvec![],
-                defs: smallvec![],
-                clobbers: PRegSet::empty(),
-                opcode: Opcode::Call,
-            }),
-        });
+        insts.extend(Self::gen_call(
+            &CallDest::ExtName(
+                ExternalName::LibCall(LibCall::Probestack),
+                abi_libcall_distance(flags),
+            ),
+            // No need to include arg here: we are post-regalloc
+            // so no constraints will be seen anyway.
+            smallvec![],
+            smallvec![],
+            PRegSet::empty(),
+            Opcode::Call,
+            // R11 is caller saved and not used for argument passing, see
+            // `gen_probestack_loop`.
+            Writable::from_reg(regs::r11()),
+            CallConv::Probestack,
+            CallConv::Probestack,
+        ));
         insts
     }
 
@@ -627,6 +656,7 @@ impl ABIMachineSpec for X64ABIMachineSpec {
     }
 
     fn gen_memcpy(
+        _flags: &settings::Flags,
         call_conv: isa::CallConv,
         dst: Reg,
         src: Reg,
@@ -704,6 +734,7 @@ impl ABIMachineSpec for X64ABIMachineSpec {
 
     fn get_ext_mode(
         _call_conv: isa::CallConv,
+        _ty: Type,
         _specified: ir::ArgumentExtension,
     ) -> ir::ArgumentExtension {
         ir::ArgumentExtension::None
@@ -51,7 +51,9 @@ pub(crate) fn int_reg_enc(reg: impl Into<Reg>) -> u8 {
     let reg = reg.into();
     debug_assert!(reg.is_real());
     debug_assert_eq!(reg.class(), RegClass::Int);
-    reg.to_real_reg().unwrap().hw_enc()
+    let enc = reg.to_real_reg().unwrap().hw_enc();
+    assert_reg_field_fits!(enc < 16, reg);
+    enc
 }
 
 /// Get the encoding number of any register.
@@ -59,7 +61,9 @@ pub(crate) fn int_reg_enc(reg: impl Into<Reg>) -> u8 {
 pub(crate) fn reg_enc(reg: impl Into<Reg>) -> u8 {
     let reg = reg.into();
     debug_assert!(reg.is_real());
-    reg.to_real_reg().unwrap().hw_enc()
+    let enc = reg.to_real_reg().unwrap().hw_enc();
+    assert_reg_field_fits!(enc < 16, reg);
+    enc
 }
 
 /// A small bit field to record a REX prefix specification:
@@ -349,6 +349,25 @@ pub(crate) fn emit(
             emit_std_enc_enc(sink, prefix, opcode, 1, subopcode, enc_src, rex_flags)
         }
 
+        Inst::Bswap { size, src, dst } => {
+            let src = allocs.next(src.to_reg());
+            let dst = allocs.next(dst.to_reg().to_reg());
+            debug_assert_eq!(src, dst);
+            // The register is encoded in the low bits of the opcode: 0F C8+rd.
+            let enc_dst = int_reg_enc(dst);
+            let rex_w = if *size == OperandSize::Size64 {
+                0x08
+            } else {
+                0
+            };
+            let rex = 0x40 | rex_w | ((enc_dst >> 3) & 1);
+            if rex != 0x40 {
+                sink.put1(rex);
+            }
+            sink.put1(0x0F);
+            sink.put1(0xC8 | (enc_dst & 7));
+        }
+
         Inst::Neg { size, src, dst } => {
             let src = allocs.next(src.to_reg());
             let dst = allocs.next(dst.to_reg().to_reg());
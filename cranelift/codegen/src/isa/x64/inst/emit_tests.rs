@@ -1694,6 +1694,29 @@ fn test_x64_emit() {
         "notb    %al, %al",
     ));
 
+    // ========================================================
+    // Bswap
+    insns.push((
+        Inst::bswap(OperandSize::Size32, Writable::from_reg(regs::rsi())),
+        "0FCE",
+        "bswapl  %esi, %esi",
+    ));
+    insns.push((
+        Inst::bswap(OperandSize::Size64, Writable::from_reg(regs::rax())),
+        "480FC8",
+        "bswapq  %rax, %rax",
+    ));
+    insns.push((
+        Inst::bswap(OperandSize::Size32, Writable::from_reg(regs::r12())),
+        "410FCC",
+        "bswapl  %r12d, %r12d",
+    ));
+    insns.push((
+        Inst::bswap(OperandSize::Size64, Writable::from_reg(regs::r15())),
+        "490FCF",
+        "bswapq  %r15, %r15",
+    ));
+
     // ========================================================
     // Neg
     insns.push((
@@ -95,6 +95,7 @@ impl Inst {
             | Inst::MovsxRmR { .. }
             | Inst::MovzxRmR { .. }
             | Inst::MulHi { .. }
+            | Inst::Bswap { .. }
             | Inst::Neg { .. }
             | Inst::Not { .. }
             | Inst::Nop { .. }
@@ -199,6 +200,17 @@ impl Inst {
         }
     }
 
+    #[allow(dead_code)]
+    pub(crate) fn bswap(size: OperandSize, src: Writable<Reg>) -> Inst {
+        debug_assert!(size.is_one_of(&[OperandSize::Size32, OperandSize::Size64]));
+        debug_assert_eq!(src.to_reg().class(), RegClass::Int);
+        Inst::Bswap {
+            size,
+            src: Gpr::new(src.to_reg()).unwrap(),
+            dst: WritableGpr::from_writable_reg(src).unwrap(),
+        }
+    }
+
     pub(crate) fn div(
         size: OperandSize,
         signed: bool,
@@ -764,6 +776,17 @@ impl PrettyPrint for Inst {
                 )
             }
 
+            Inst::Bswap { size, src, dst } => {
+                let src = pretty_print_reg(src.to_reg(), size.to_bytes(), allocs);
+                let dst = pretty_print_reg(dst.to_reg().to_reg(), size.to_bytes(), allocs);
+                format!(
+                    "{} {}, {}",
+                    ljustify2("bswap".to_string(), suffix_lq(*size)),
+                    src,
+                    dst,
+                )
+            }
+
             Inst::Neg { size, src, dst } => {
                 let src = pretty_print_reg(src.to_reg(), size.to_bytes(), allocs);
                 let dst = pretty_print_reg(dst.to_reg().to_reg(), size.to_bytes(), allocs);
@@ -1665,7 +1688,7 @@ fn x64_get_operands<F: Fn(VReg) -> VReg>(inst: &Inst, collector: &mut OperandCol
             collector.reg_use(src.to_reg());
             collector.reg_reuse_def(dst.to_writable_reg(), 0);
         }
-        Inst::Neg { src, dst, .. } => {
+        Inst::Neg { src, dst, .. } | Inst::Bswap { src, dst, .. } => {
             collector.reg_use(src.to_reg());
             collector.reg_reuse_def(dst.to_writable_reg(), 0);
         }
@@ -2211,6 +2234,12 @@ impl MachInst for Inst {
         Inst::nop(std::cmp::min(preferred_size, 15) as u8)
     }
 
+    /// Loop bodies starting on a 16-byte boundary make the best use of each
+    /// instruction fetch block.
+    fn loop_header_alignment() -> CodeOffset {
+        16
+    }
+
     fn rc_for_type(ty: Type) -> CodegenResult<(&'static [RegClass], &'static [Type])> {
         match ty {
             types::I8 => Ok((&[RegClass::Int], &[types::I8])),
@@ -12,7 +12,7 @@ use crate::machinst::abi::SmallInstVec;
 use crate::machinst::lower::*;
 use crate::machinst::*;
 use crate::result::CodegenResult;
-use crate::settings::Flags;
+use crate::settings::{CodeModel, Flags};
 use smallvec::{smallvec, SmallVec};
 use target_lexicon::Triple;
 
@@ -145,7 +145,7 @@ fn emit_vm_call(
 ) -> CodegenResult<()> {
     let extname = ExternalName::LibCall(libcall);
 
-    let dist = if flags.use_colocated_libcalls() {
+    let dist = if flags.use_colocated_libcalls() && flags.code_model() != CodeModel::Large {
         RelocDistance::Near
     } else {
         RelocDistance::Far
@@ -365,6 +365,7 @@ fn lower_insn_to_regs(
         | Opcode::Ctz
         | Opcode::Popcnt
         | Opcode::Bitrev
+        | Opcode::Bswap
         | Opcode::IsNull
         | Opcode::IsInvalid
         | Opcode::Uextend
@@ -473,19 +474,7 @@ fn lower_insn_to_regs(
         | Opcode::TlsValue
         | Opcode::SqmulRoundSat
         | Opcode::Uunarrow
-        | Opcode::Nop => {
-            let ty = if outputs.len() > 0 {
-                Some(ctx.output_ty(insn, 0))
-            } else {
-                None
-            };
-
-            unreachable!(
-                "implemented in ISLE: inst = `{}`, type = `{:?}`",
-                ctx.dfg().display_inst(insn),
-                ty
-            )
-        }
+        | Opcode::Nop => Err(ctx.no_lowering(insn)),
 
         Opcode::DynamicStackAddr => unimplemented!("DynamicStackAddr"),
 
@@ -626,10 +615,7 @@ impl LowerBackend for X64Backend {
             return Ok(());
         }
 
-        unreachable!(
-            "implemented in ISLE: branch = `{}`",
-            ctx.dfg().display_inst(branches[0]),
-        );
+        Err(ctx.no_lowering(branches[0]))
     }
 
     fn maybe_pinned_reg(&self) -> Option<Reg> {
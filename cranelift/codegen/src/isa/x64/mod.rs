@@ -2,7 +2,7 @@
 
 use self::inst::EmitInfo;
 
-use super::TargetIsa;
+use super::{CallConv, Capabilities, TargetIsa};
 use crate::ir::{condcodes::IntCC, Function, Type};
 #[cfg(feature = "unwind")]
 use crate::isa::unwind::systemv;
@@ -68,7 +68,12 @@ impl TargetIsa for X64Backend {
         let flags = self.flags();
         let (vcode, regalloc_result) = self.compile_vcode(func, flags.clone())?;
 
-        let emit_result = vcode.emit(&regalloc_result, want_disasm, flags.machine_code_cfg_info());
+        let emit_result = vcode.emit(
+            &regalloc_result,
+            want_disasm,
+            flags.machine_code_cfg_info(),
+            flags.align_loops(),
+        );
         let frame_size = emit_result.frame_size;
         let value_labels_ranges = emit_result.value_labels_ranges;
         let buffer = emit_result.buffer.finish();
@@ -104,6 +109,23 @@ impl TargetIsa for X64Backend {
         16
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            i128: true,
+            simd: self.flags.enable_simd(),
+            dynamic_vectors: false,
+            atomics: true,
+            call_conventions: &[
+                CallConv::Fast,
+                CallConv::Cold,
+                CallConv::SystemV,
+                CallConv::WindowsFastcall,
+                CallConv::WasmtimeSystemV,
+                CallConv::WasmtimeFastcall,
+            ],
+        }
+    }
+
     fn name(&self) -> &'static str {
         "x64"
     }
@@ -216,7 +238,10 @@ mod test {
     use super::*;
     use crate::cursor::{Cursor, FuncCursor};
     use crate::ir::{types::*, RelSourceLoc, SourceLoc, UserFuncName, ValueLabel, ValueLabelStart};
-    use crate::ir::{AbiParam, Function, InstBuilder, JumpTableData, Signature};
+    use crate::ir::{
+        AbiParam, ExtFuncData, ExternalName, Function, InstBuilder, JumpTableData, Signature,
+        TrapCode,
+    };
     use crate::isa::CallConv;
     use crate::settings;
     use crate::settings::Configurable;
@@ -363,6 +388,206 @@ mod test {
         assert_eq!(code, &golden[..]);
     }
 
+    /// Value label aliases are followed with a bounded loop, so lowering must finish on alias
+    /// chains far longer than the limit, and on cyclic ones.
+    #[test]
+    fn long_value_label_alias_chain() {
+        let name = UserFuncName::testcase("test0");
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(I64));
+        sig.returns.push(AbiParam::new(I64));
+        let mut func = Function::with_name_signature(name, sig);
+        func.dfg.collect_debug_info();
+
+        let bb0 = func.dfg.make_block();
+        let arg0 = func.dfg.append_block_param(bb0, I64);
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(bb0);
+        let chain: Vec<_> = (0..10_000).map(|i| pos.ins().iconst(I64, i)).collect();
+        let cycle = [pos.ins().iconst(I64, 0), pos.ins().iconst(I64, 1)];
+        pos.ins().return_(&[arg0]);
+
+        let values_labels = func.dfg.values_labels.as_mut().unwrap();
+        values_labels.insert(
+            arg0,
+            crate::ir::ValueLabelAssignments::Alias {
+                from: RelSourceLoc::new(0),
+                value: chain[0],
+            },
+        );
+        for pair in chain.windows(2) {
+            values_labels.insert(
+                pair[0],
+                crate::ir::ValueLabelAssignments::Alias {
+                    from: RelSourceLoc::new(0),
+                    value: pair[1],
+                },
+            );
+        }
+        values_labels.insert(
+            *chain.last().unwrap(),
+            crate::ir::ValueLabelAssignments::Starts(vec![ValueLabelStart {
+                from: RelSourceLoc::new(0),
+                label: ValueLabel::new(1),
+            }]),
+        );
+        values_labels.insert(
+            cycle[0],
+            crate::ir::ValueLabelAssignments::Alias {
+                from: RelSourceLoc::new(0),
+                value: cycle[1],
+            },
+        );
+        values_labels.insert(
+            cycle[1],
+            crate::ir::ValueLabelAssignments::Alias {
+                from: RelSourceLoc::new(0),
+                value: cycle[0],
+            },
+        );
+
+        let mut shared_flags_builder = settings::builder();
+        shared_flags_builder.set("opt_level", "none").unwrap();
+        let shared_flags = settings::Flags::new(shared_flags_builder);
+        let isa_flags = x64_settings::Flags::new(&shared_flags, x64_settings::builder());
+        let backend = X64Backend::new_with_flags(
+            Triple::from_str("x86_64").unwrap(),
+            shared_flags,
+            isa_flags,
+        );
+        backend
+            .compile_function(&func, /* want_disasm = */ false)
+            .unwrap();
+    }
+
+    #[test]
+    fn emit_to_memory() {
+        let name = UserFuncName::testcase("test0");
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(I64));
+        let mut func = Function::with_name_signature(name, sig);
+        let signature = func.import_signature(Signature::new(CallConv::SystemV));
+        let callee = func.import_function(ExtFuncData {
+            name: ExternalName::testcase("callee"),
+            signature,
+            colocated: false,
+        });
+
+        let bb0 = func.dfg.make_block();
+        let arg0 = func.dfg.append_block_param(bb0, I64);
+        let bb1 = func.dfg.make_block();
+        let bb2 = func.dfg.make_block();
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(bb0);
+        pos.ins().call(callee, &[]);
+        pos.ins().brz(arg0, bb1, &[]);
+        pos.ins().jump(bb2, &[]);
+        pos.insert_block(bb1);
+        pos.ins().trap(TrapCode::User(0));
+        pos.insert_block(bb2);
+        pos.ins().return_(&[]);
+
+        let shared_flags = settings::Flags::new(settings::builder());
+        let isa_flags = x64_settings::Flags::new(&shared_flags, x64_settings::builder());
+        let backend = X64Backend::new_with_flags(
+            Triple::from_str("x86_64").unwrap(),
+            shared_flags,
+            isa_flags,
+        );
+        let result = backend
+            .compile_function(&func, /* want_disasm = */ false)
+            .unwrap();
+
+        let mut mem = vec![0; result.code_info().total_size as usize];
+        let mut relocs = Vec::new();
+        let mut traps = Vec::new();
+        unsafe {
+            result.emit_to_memory(
+                mem.as_mut_ptr(),
+                |reloc| relocs.push(reloc.clone()),
+                |trap| traps.push(trap.clone()),
+            );
+        }
+        assert_eq!(mem, result.code_buffer());
+        assert_eq!(relocs, result.buffer.relocs());
+        assert_eq!(traps, result.buffer.traps());
+        assert_eq!(relocs.len(), 1);
+        assert!(traps.iter().any(|trap| trap.code == TrapCode::User(0)));
+    }
+
+    #[test]
+    fn peak_ir_size() {
+        let name = UserFuncName::testcase("test0");
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(I64));
+        sig.returns.push(AbiParam::new(I64));
+        let mut func = Function::with_name_signature(name, sig);
+
+        let bb0 = func.dfg.make_block();
+        let arg0 = func.dfg.append_block_param(bb0, I64);
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(bb0);
+        let v1 = pos.ins().iadd_imm(arg0, 1);
+        pos.ins().return_(&[v1]);
+
+        let shared_flags = settings::Flags::new(settings::builder());
+        let isa_flags = x64_settings::Flags::new(&shared_flags, x64_settings::builder());
+        let backend = X64Backend::new_with_flags(
+            Triple::from_str("x86_64").unwrap(),
+            shared_flags,
+            isa_flags,
+        );
+        let mut ctx = crate::Context::for_function(func);
+        assert_eq!(ctx.peak_ir_size(), crate::IrSize::default());
+        ctx.compile(&backend).unwrap();
+
+        // Legalization turns the `iadd_imm` into an `iconst` and an `iadd`.
+        assert_eq!(
+            ctx.peak_ir_size(),
+            crate::IrSize {
+                insts: 3,
+                values: 3,
+                blocks: 1,
+            }
+        );
+        ctx.clear();
+        assert_eq!(ctx.peak_ir_size(), crate::IrSize::default());
+    }
+
+    #[test]
+    fn variadic_call_unsupported_call_conv() {
+        let name = UserFuncName::testcase("test0");
+        let mut func = Function::with_name_signature(name, Signature::new(CallConv::SystemV));
+        let mut callee_sig = Signature::new(CallConv::WindowsFastcall);
+        callee_sig.params.push(AbiParam::new(I64));
+        callee_sig.variadic = Some(1);
+        let signature = func.import_signature(callee_sig);
+        let callee = func.import_function(ExtFuncData {
+            name: ExternalName::testcase("callee"),
+            signature,
+            colocated: false,
+        });
+
+        let bb0 = func.dfg.make_block();
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(bb0);
+        let v0 = pos.ins().iconst(I64, 0);
+        pos.ins().call(callee, &[v0]);
+        pos.ins().return_(&[]);
+
+        let shared_flags = settings::Flags::new(settings::builder());
+        let isa_flags = x64_settings::Flags::new(&shared_flags, x64_settings::builder());
+        let backend = X64Backend::new_with_flags(
+            Triple::from_str("x86_64").unwrap(),
+            shared_flags,
+            isa_flags,
+        );
+        assert!(matches!(
+            backend.compile_function(&func, /* want_disasm = */ false),
+            Err(CodegenError::Unsupported(_)),
+        ));
+    }
+
     // Check that feature tests for SIMD work correctly.
     #[test]
     fn simd_required_features() {
@@ -467,4 +692,66 @@ mod test {
 
         assert_eq!(code, &golden[..]);
     }
+
+    #[test]
+    fn test_capabilities() {
+        let isa = crate::isa::lookup_by_name("x86_64").unwrap();
+        let capabilities = isa
+            .finish(settings::Flags::new(settings::builder()))
+            .unwrap()
+            .capabilities();
+        assert!(capabilities.i128);
+        assert!(!capabilities.simd);
+        assert!(capabilities
+            .call_conventions
+            .contains(&CallConv::WindowsFastcall));
+        assert!(!capabilities
+            .call_conventions
+            .contains(&CallConv::AppleAarch64));
+
+        let mut shared_flags = settings::builder();
+        shared_flags.enable("enable_simd").unwrap();
+        let mut isa = crate::isa::lookup_by_name("x86_64").unwrap();
+        for flag in ["has_sse3", "has_ssse3", "has_sse41", "has_sse42"] {
+            isa.enable(flag).unwrap();
+        }
+        let capabilities = isa
+            .finish(settings::Flags::new(shared_flags))
+            .unwrap()
+            .capabilities();
+        assert!(capabilities.simd);
+
+        // Every advertised calling convention must be usable, both for the function itself and
+        // for the functions it calls.
+        for &call_conv in capabilities.call_conventions {
+            let mut sig = Signature::new(call_conv);
+            sig.params.push(AbiParam::new(I64));
+            sig.returns.push(AbiParam::new(I64));
+            let mut func =
+                Function::with_name_signature(UserFuncName::testcase("test0"), sig.clone());
+            let callee_sig = func.import_signature(sig);
+            let callee = func.import_function(ExtFuncData {
+                name: ExternalName::testcase("callee"),
+                signature: callee_sig,
+                colocated: false,
+            });
+
+            let bb0 = func.dfg.make_block();
+            let arg0 = func.dfg.append_block_param(bb0, I64);
+            let mut pos = FuncCursor::new(&mut func);
+            pos.insert_block(bb0);
+            let v1 = pos.ins().iadd_imm(arg0, 1);
+            let call = pos.ins().call(callee, &[v1]);
+            let v2 = pos.func.dfg.first_result(call);
+            pos.ins().return_(&[v2]);
+
+            let isa = crate::isa::lookup_by_name("x86_64")
+                .unwrap()
+                .finish(settings::Flags::new(settings::builder()))
+                .unwrap();
+            crate::Context::for_function(func)
+                .compile(&*isa)
+                .unwrap_or_else(|e| panic!("failed to compile for {}: {:?}", call_conv, e.inner));
+        }
+    }
 }
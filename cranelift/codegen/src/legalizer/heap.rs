@@ -4,13 +4,59 @@
 //! instruction into code that depends on the kind of heap referenced.
 
 use crate::cursor::{Cursor, FuncCursor};
+use crate::dominator_tree::DominatorTree;
+use crate::entity::EntitySet;
 use crate::flowgraph::ControlFlowGraph;
+use crate::fx::FxHashMap;
 use crate::ir::condcodes::IntCC;
 use crate::ir::immediates::Uimm32;
-use crate::ir::{self, InstBuilder, RelSourceLoc};
+use crate::ir::{self, InstBuilder, InstructionData, Opcode, RelSourceLoc, ValueDef};
 use crate::isa::TargetIsa;
+use alloc::vec::Vec;
+
+/// Find the `heap_addr` instructions whose bounds check repeats an earlier one.
+///
+/// That is the case when a `heap_addr` of the same static heap and index, accessing at least as
+/// many bytes, dominates the instruction: if the earlier access didn't trap, this one won't
+/// either. Wasm loops commonly access the same index several times in a row.
+///
+/// Dynamic heaps are left alone, since their bound can move between the two accesses.
+pub fn redundant_heap_checks(func: &ir::Function, domtree: &DominatorTree) -> EntitySet<ir::Inst> {
+    let mut checks: FxHashMap<(ir::Heap, ir::Value), Vec<(ir::Inst, u32)>> = FxHashMap::default();
+    let mut redundant = EntitySet::new();
+
+    // Visiting the blocks in reverse post-order visits each check after those dominating it.
+    for &block in domtree.cfg_postorder().iter().rev() {
+        for inst in func.layout.block_insts(block) {
+            let (heap, offset, access_size) = match func.dfg[inst] {
+                InstructionData::HeapAddr {
+                    opcode: Opcode::HeapAddr,
+                    heap,
+                    arg,
+                    imm,
+                } => (heap, arg, u32::from(imm)),
+                _ => continue,
+            };
+            if let ir::HeapStyle::Dynamic { .. } = func.heaps[heap].style {
+                continue;
+            }
+            let earlier = checks.entry((heap, offset)).or_default();
+            if earlier.iter().any(|&(check, size)| {
+                size >= access_size && domtree.dominates(check, inst, &func.layout)
+            }) {
+                redundant.insert(inst);
+            } else {
+                earlier.push((inst, access_size));
+            }
+        }
+    }
+    redundant
+}
 
 /// Expand a `heap_addr` instruction according to the definition of the heap.
+///
+/// If `checked` is true, an earlier access already did this instruction's bounds check, so it is
+/// left out.
 pub fn expand_heap_addr(
     inst: ir::Inst,
     func: &mut ir::Function,
@@ -19,6 +65,7 @@ pub fn expand_heap_addr(
     heap: ir::Heap,
     offset: ir::Value,
     access_size: Uimm32,
+    checked: bool,
 ) {
     match func.heaps[heap].style {
         ir::HeapStyle::Dynamic { bound_gv } => dynamic_addr(
@@ -39,6 +86,7 @@ pub fn expand_heap_addr(
             bound.into(),
             func,
             cfg,
+            checked,
         ),
     }
 }
@@ -56,11 +104,19 @@ fn dynamic_addr(
     let offset_ty = func.dfg.value_type(offset);
     let addr_ty = func.dfg.value_type(func.dfg.first_result(inst));
     let min_size = func.heaps[heap].min_size.into();
+    let max_offset = max_unsigned_value(func, offset, MAX_VALUE_RANGE_DEPTH);
     let mut pos = FuncCursor::new(func).at_inst(inst);
     pos.use_srcloc(inst);
 
     let offset = cast_offset_to_pointer_ty(offset, offset_ty, addr_ty, &mut pos);
 
+    // The bound is never smaller than the heap's minimum size, so no check is needed if the
+    // offset is known to stay below `min_size - access_size`.
+    if access_size <= min_size && max_offset <= min_size - access_size {
+        compute_addr(isa, inst, heap, addr_ty, offset, pos.func, None);
+        return;
+    }
+
     // Start with the bounds check. Trap if `offset + access_size > bound`.
     let bound = pos.ins().global_value(addr_ty, bound_gv);
     let (cc, lhs, bound) = if access_size == 1 {
@@ -112,9 +168,11 @@ fn static_addr(
     bound: u64,
     func: &mut ir::Function,
     cfg: &mut ControlFlowGraph,
+    checked: bool,
 ) {
     let offset_ty = func.dfg.value_type(offset);
     let addr_ty = func.dfg.value_type(func.dfg.first_result(inst));
+    let max_offset = max_unsigned_value(func, offset, MAX_VALUE_RANGE_DEPTH);
     let mut pos = FuncCursor::new(func).at_inst(inst);
     pos.use_srcloc(inst);
 
@@ -149,10 +207,13 @@ fn static_addr(
     //
     // With that we have an optimization here where with 32-bit offsets and
     // `bound - access_size >= 4GB` we can omit a bounds check.
+    //
+    // More generally, the check can be omitted whenever the offset is known
+    // to be at most `bound - access_size`, for example because it was masked.
     let limit = bound - access_size;
     let mut spectre_oob_comparison = None;
     offset = cast_offset_to_pointer_ty(offset, offset_ty, addr_ty, &mut pos);
-    if offset_ty != ir::types::I32 || limit < 0xffff_ffff {
+    if max_offset > limit {
         // Here we want to test the condition `offset > limit` and if that's
         // true then this is an out-of-bounds access and needs to trap. For ARM
         // and other RISC architectures it's easier to test against an immediate
@@ -172,8 +233,12 @@ fn static_addr(
             let limit = limit as i64;
             (IntCC::UnsignedGreaterThan, offset, limit)
         };
-        let oob = pos.ins().icmp_imm(cc, lhs, limit_imm);
-        pos.ins().trapnz(oob, ir::TrapCode::HeapOutOfBounds);
+        if !checked {
+            let oob = pos.ins().icmp_imm(cc, lhs, limit_imm);
+            pos.ins().trapnz(oob, ir::TrapCode::HeapOutOfBounds);
+        }
+        // The Spectre guard is still needed when the check is done by an earlier access, since
+        // speculative execution may have gone past that check.
         if isa.flags().enable_heap_access_spectre_mitigation() {
             let limit = pos.ins().iconst(addr_ty, limit_imm);
             spectre_oob_comparison = Some((cc, lhs, limit));
@@ -191,6 +256,76 @@ fn static_addr(
     );
 }
 
+/// How many instructions deep `max_unsigned_value` looks for the definition of a value.
+const MAX_VALUE_RANGE_DEPTH: u32 = 4;
+
+/// Compute an upper bound of the unsigned value of `value`, by looking at the instructions that
+/// compute it, up to `depth` levels deep.
+///
+/// This is a simple known-bits analysis: Wasm producers often mask heap indices, or use
+/// constant ones, which makes their bounds checks redundant.
+fn max_unsigned_value(func: &ir::Function, value: ir::Value, depth: u32) -> u64 {
+    let ty = func.dfg.value_type(value);
+    let type_max = if ty.bits() >= 64 {
+        u64::MAX
+    } else {
+        (1 << ty.bits()) - 1
+    };
+    let inst = match func.dfg.value_def(value) {
+        ValueDef::Result(inst, 0) if depth > 0 => inst,
+        _ => return type_max,
+    };
+    let max = match func.dfg[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => imm.bits() as u64,
+        InstructionData::BinaryImm64 {
+            opcode: Opcode::BandImm,
+            arg,
+            imm,
+        } => (imm.bits() as u64).min(max_unsigned_value(func, arg, depth - 1)),
+        InstructionData::BinaryImm64 {
+            opcode: Opcode::UshrImm,
+            arg,
+            imm,
+        } => {
+            let shift = imm.bits() as u32 & (ty.bits() - 1);
+            max_unsigned_value(func, arg, depth - 1) >> shift
+        }
+        InstructionData::Binary {
+            opcode: Opcode::Ushr,
+            args,
+        } => match func.dfg.value_def(args[1]) {
+            ValueDef::Result(shift_inst, 0) => match func.dfg[shift_inst] {
+                InstructionData::UnaryImm {
+                    opcode: Opcode::Iconst,
+                    imm,
+                } => {
+                    let shift = imm.bits() as u32 & (ty.bits() - 1);
+                    max_unsigned_value(func, args[0], depth - 1) >> shift
+                }
+                _ => type_max,
+            },
+            _ => type_max,
+        },
+        InstructionData::Binary {
+            opcode: Opcode::Band,
+            args,
+        } => {
+            let lhs = max_unsigned_value(func, args[0], depth - 1);
+            let rhs = max_unsigned_value(func, args[1], depth - 1);
+            lhs.min(rhs)
+        }
+        InstructionData::Unary {
+            opcode: Opcode::Uextend,
+            arg,
+        } => max_unsigned_value(func, arg, depth - 1),
+        _ => type_max,
+    };
+    max & type_max
+}
+
 fn cast_offset_to_pointer_ty(
     offset: ir::Value,
     offset_ty: ir::Type,
@@ -14,18 +14,24 @@
 //! from the encoding recipes, and solved later by the register allocator.
 
 use crate::cursor::{Cursor, FuncCursor};
+use crate::dominator_tree::DominatorTree;
+use crate::entity::EntitySet;
 use crate::flowgraph::ControlFlowGraph;
+use crate::ir::condcodes::IntCC;
 use crate::ir::immediates::Imm64;
-use crate::ir::types::{I128, I64};
+use crate::ir::types::{I128, I16, I32, I64, I8};
 use crate::ir::{self, InstBuilder, InstructionData, MemFlags, Value};
 use crate::isa::TargetIsa;
+use crate::result::{CodegenError, CodegenResult};
+use crate::settings::OptLevel;
+use alloc::string::ToString;
 
 mod globalvalue;
 mod heap;
 mod table;
 
 use self::globalvalue::expand_global_value;
-use self::heap::expand_heap_addr;
+use self::heap::{expand_heap_addr, redundant_heap_checks};
 use self::table::expand_table_addr;
 
 fn imm_const(pos: &mut FuncCursor, arg: Value, imm: Imm64, is_signed: bool) -> Value {
@@ -43,229 +49,422 @@ fn imm_const(pos: &mut FuncCursor, arg: Value, imm: Imm64, is_signed: bool) -> V
     }
 }
 
+/// The maximum number of expansions in a row that may happen without the cursor moving past a
+/// legal instruction. Real expansions only take a few steps to converge; hitting this limit means
+/// an expansion keeps re-creating an instruction that is expanded again.
+const MAX_EXPANSIONS_WITHOUT_PROGRESS: u32 = 1000;
+
 /// Perform a simple legalization by expansion of the function, without
 /// platform-specific transforms.
-pub fn simple_legalize(func: &mut ir::Function, cfg: &mut ControlFlowGraph, isa: &dyn TargetIsa) {
+///
+/// `domtree` is only used as scratch space for finding redundant heap bounds checks, and is left
+/// in an unspecified state.
+pub fn simple_legalize(
+    func: &mut ir::Function,
+    cfg: &mut ControlFlowGraph,
+    domtree: &mut DominatorTree,
+    isa: &dyn TargetIsa,
+) -> CodegenResult<()> {
+    let checked_heap_addrs = if isa.flags().opt_level() == OptLevel::None || func.heaps.is_empty() {
+        EntitySet::new()
+    } else {
+        domtree.compute(func, cfg);
+        redundant_heap_checks(func, domtree)
+    };
+    legalize_with(func, |pos, inst| {
+        legalize_inst(pos, inst, cfg, isa, &checked_heap_addrs)
+    })
+}
+
+/// Visit every instruction in `func`, calling `expand` on it. `expand` returns whether it
+/// replaced the instruction, in which case the instructions it produced are visited again.
+fn legalize_with(
+    func: &mut ir::Function,
+    mut expand: impl FnMut(&mut FuncCursor, ir::Inst) -> bool,
+) -> CodegenResult<()> {
     let mut pos = FuncCursor::new(func);
     let func_begin = pos.position();
     pos.set_position(func_begin);
     while let Some(_block) = pos.next_block() {
         let mut prev_pos = pos.position();
+        let mut expansions = 0;
         while let Some(inst) = pos.next_inst() {
-            match pos.func.dfg[inst] {
-                // control flow
-                InstructionData::BranchIcmp {
-                    opcode: ir::Opcode::BrIcmp,
-                    cond,
-                    destination,
-                    ref args,
-                } => {
-                    let a = args.get(0, &pos.func.dfg.value_lists).unwrap();
-                    let b = args.get(1, &pos.func.dfg.value_lists).unwrap();
-                    let block_args = args.as_slice(&pos.func.dfg.value_lists)[2..].to_vec();
-
-                    let old_block = pos.func.layout.pp_block(inst);
-                    pos.func.dfg.clear_results(inst);
-
-                    let icmp_res = pos.func.dfg.replace(inst).icmp(cond, a, b);
-                    let mut pos = FuncCursor::new(pos.func).after_inst(inst);
-                    pos.use_srcloc(inst);
-                    pos.ins().brnz(icmp_res, destination, &block_args);
-
-                    cfg.recompute_block(pos.func, destination);
-                    cfg.recompute_block(pos.func, old_block);
+            if expansions == MAX_EXPANSIONS_WITHOUT_PROGRESS {
+                return Err(CodegenError::LegalizerLoop(
+                    pos.func.dfg.display_inst(inst).to_string(),
+                ));
+            }
+            if expand(&mut pos, inst) {
+                // Legalization implementations require fixpoint loop here.
+                // TODO: fix this.
+                pos.set_position(prev_pos);
+                expansions += 1;
+            } else {
+                prev_pos = pos.position();
+                expansions = 0;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Expand `inst` if it needs legalizing, returning whether it did.
+///
+/// The bounds checks of the `heap_addr` instructions in `checked_heap_addrs` are left out.
+fn legalize_inst(
+    pos: &mut FuncCursor,
+    inst: ir::Inst,
+    cfg: &mut ControlFlowGraph,
+    isa: &dyn TargetIsa,
+    checked_heap_addrs: &EntitySet<ir::Inst>,
+) -> bool {
+    match pos.func.dfg[inst] {
+        // control flow
+        InstructionData::BranchIcmp {
+            opcode: ir::Opcode::BrIcmp,
+            cond,
+            destination,
+            ref args,
+        } => {
+            let a = args.get(0, &pos.func.dfg.value_lists).unwrap();
+            let b = args.get(1, &pos.func.dfg.value_lists).unwrap();
+            let block_args = args.as_slice(&pos.func.dfg.value_lists)[2..].to_vec();
+
+            let old_block = pos.func.layout.pp_block(inst);
+            pos.func.dfg.clear_results(inst);
+
+            let icmp_res = pos.func.dfg.replace(inst).icmp(cond, a, b);
+            let mut pos = FuncCursor::new(pos.func).after_inst(inst);
+            pos.use_srcloc(inst);
+            pos.ins().brnz(icmp_res, destination, &block_args);
+
+            cfg.recompute_block(pos.func, destination);
+            cfg.recompute_block(pos.func, old_block);
+        }
+        InstructionData::CondTrap {
+            opcode: opcode @ (ir::Opcode::Trapnz | ir::Opcode::Trapz | ir::Opcode::ResumableTrapnz),
+            arg,
+            code,
+        } => {
+            expand_cond_trap(inst, pos.func, cfg, opcode, arg, code);
+        }
+
+        // memory and constants
+        InstructionData::UnaryGlobalValue {
+            opcode: ir::Opcode::GlobalValue,
+            global_value,
+        } => expand_global_value(inst, pos.func, isa, global_value),
+        InstructionData::HeapAddr {
+            opcode: ir::Opcode::HeapAddr,
+            heap,
+            arg,
+            imm,
+        } => {
+            let checked = checked_heap_addrs.contains(inst);
+            expand_heap_addr(inst, pos.func, cfg, isa, heap, arg, imm, checked)
+        }
+        InstructionData::StackLoad {
+            opcode: ir::Opcode::StackLoad,
+            stack_slot,
+            offset,
+        } => {
+            let ty = pos.func.dfg.value_type(pos.func.dfg.first_result(inst));
+            let addr_ty = isa.pointer_type();
+
+            let mut pos = FuncCursor::new(pos.func).at_inst(inst);
+            pos.use_srcloc(inst);
+
+            let addr = pos.ins().stack_addr(addr_ty, stack_slot, offset);
+
+            // Stack slots are required to be accessible and aligned.
+            let mflags = MemFlags::trusted();
+            pos.func.dfg.replace(inst).load(ty, mflags, addr, 0);
+        }
+        InstructionData::StackStore {
+            opcode: ir::Opcode::StackStore,
+            arg,
+            stack_slot,
+            offset,
+        } => {
+            let addr_ty = isa.pointer_type();
+
+            let mut pos = FuncCursor::new(pos.func).at_inst(inst);
+            pos.use_srcloc(inst);
+
+            let addr = pos.ins().stack_addr(addr_ty, stack_slot, offset);
+
+            let mut mflags = MemFlags::new();
+            // Stack slots are required to be accessible and aligned.
+            mflags.set_notrap();
+            mflags.set_aligned();
+            pos.func.dfg.replace(inst).store(mflags, arg, addr, 0);
+        }
+        InstructionData::DynamicStackLoad {
+            opcode: ir::Opcode::DynamicStackLoad,
+            dynamic_stack_slot,
+        } => {
+            let ty = pos.func.dfg.value_type(pos.func.dfg.first_result(inst));
+            assert!(ty.is_dynamic_vector());
+            let addr_ty = isa.pointer_type();
+
+            let mut pos = FuncCursor::new(pos.func).at_inst(inst);
+            pos.use_srcloc(inst);
+
+            let addr = pos.ins().dynamic_stack_addr(addr_ty, dynamic_stack_slot);
+
+            // Stack slots are required to be accessible and aligned.
+            let mflags = MemFlags::trusted();
+            pos.func.dfg.replace(inst).load(ty, mflags, addr, 0);
+        }
+        InstructionData::DynamicStackStore {
+            opcode: ir::Opcode::DynamicStackStore,
+            arg,
+            dynamic_stack_slot,
+        } => {
+            pos.use_srcloc(inst);
+            let addr_ty = isa.pointer_type();
+            let vector_ty = pos.func.dfg.value_type(arg);
+            assert!(vector_ty.is_dynamic_vector());
+
+            let addr = pos.ins().dynamic_stack_addr(addr_ty, dynamic_stack_slot);
+
+            let mut mflags = MemFlags::new();
+            // Stack slots are required to be accessible and aligned.
+            mflags.set_notrap();
+            mflags.set_aligned();
+            pos.func.dfg.replace(inst).store(mflags, arg, addr, 0);
+        }
+        InstructionData::TableAddr {
+            opcode: ir::Opcode::TableAddr,
+            table,
+            arg,
+            offset,
+        } => expand_table_addr(isa, inst, pos.func, table, arg, offset),
+
+        InstructionData::BinaryImm64 { opcode, arg, imm } => {
+            let is_signed = matches!(
+                opcode,
+                ir::Opcode::IaddImm
+                    | ir::Opcode::IrsubImm
+                    | ir::Opcode::ImulImm
+                    | ir::Opcode::SdivImm
+                    | ir::Opcode::SremImm
+                    | ir::Opcode::IfcmpImm
+            );
+
+            let imm = imm_const(pos, arg, imm, is_signed);
+            let replace = pos.func.dfg.replace(inst);
+            match opcode {
+                // bitops
+                ir::Opcode::BandImm => {
+                    replace.band(arg, imm);
                 }
-                InstructionData::CondTrap {
-                    opcode:
-                        opcode @ (ir::Opcode::Trapnz | ir::Opcode::Trapz | ir::Opcode::ResumableTrapnz),
-                    arg,
-                    code,
-                } => {
-                    expand_cond_trap(inst, &mut pos.func, cfg, opcode, arg, code);
+                ir::Opcode::BorImm => {
+                    replace.bor(arg, imm);
                 }
-
-                // memory and constants
-                InstructionData::UnaryGlobalValue {
-                    opcode: ir::Opcode::GlobalValue,
-                    global_value,
-                } => expand_global_value(inst, &mut pos.func, isa, global_value),
-                InstructionData::HeapAddr {
-                    opcode: ir::Opcode::HeapAddr,
-                    heap,
-                    arg,
-                    imm,
-                } => expand_heap_addr(inst, &mut pos.func, cfg, isa, heap, arg, imm),
-                InstructionData::StackLoad {
-                    opcode: ir::Opcode::StackLoad,
-                    stack_slot,
-                    offset,
-                } => {
-                    let ty = pos.func.dfg.value_type(pos.func.dfg.first_result(inst));
-                    let addr_ty = isa.pointer_type();
-
-                    let mut pos = FuncCursor::new(pos.func).at_inst(inst);
-                    pos.use_srcloc(inst);
-
-                    let addr = pos.ins().stack_addr(addr_ty, stack_slot, offset);
-
-                    // Stack slots are required to be accessible and aligned.
-                    let mflags = MemFlags::trusted();
-                    pos.func.dfg.replace(inst).load(ty, mflags, addr, 0);
+                ir::Opcode::BxorImm => {
+                    replace.bxor(arg, imm);
                 }
-                InstructionData::StackStore {
-                    opcode: ir::Opcode::StackStore,
-                    arg,
-                    stack_slot,
-                    offset,
-                } => {
-                    let addr_ty = isa.pointer_type();
-
-                    let mut pos = FuncCursor::new(pos.func).at_inst(inst);
-                    pos.use_srcloc(inst);
-
-                    let addr = pos.ins().stack_addr(addr_ty, stack_slot, offset);
-
-                    let mut mflags = MemFlags::new();
-                    // Stack slots are required to be accessible and aligned.
-                    mflags.set_notrap();
-                    mflags.set_aligned();
-                    pos.func.dfg.replace(inst).store(mflags, arg, addr, 0);
+                // bitshifting
+                ir::Opcode::IshlImm => {
+                    replace.ishl(arg, imm);
                 }
-                InstructionData::DynamicStackLoad {
-                    opcode: ir::Opcode::DynamicStackLoad,
-                    dynamic_stack_slot,
-                } => {
-                    let ty = pos.func.dfg.value_type(pos.func.dfg.first_result(inst));
-                    assert!(ty.is_dynamic_vector());
-                    let addr_ty = isa.pointer_type();
-
-                    let mut pos = FuncCursor::new(pos.func).at_inst(inst);
-                    pos.use_srcloc(inst);
-
-                    let addr = pos.ins().dynamic_stack_addr(addr_ty, dynamic_stack_slot);
-
-                    // Stack slots are required to be accessible and aligned.
-                    let mflags = MemFlags::trusted();
-                    pos.func.dfg.replace(inst).load(ty, mflags, addr, 0);
+                ir::Opcode::RotlImm => {
+                    replace.rotl(arg, imm);
                 }
-                InstructionData::DynamicStackStore {
-                    opcode: ir::Opcode::DynamicStackStore,
-                    arg,
-                    dynamic_stack_slot,
-                } => {
-                    pos.use_srcloc(inst);
-                    let addr_ty = isa.pointer_type();
-                    let vector_ty = pos.func.dfg.value_type(arg);
-                    assert!(vector_ty.is_dynamic_vector());
-
-                    let addr = pos.ins().dynamic_stack_addr(addr_ty, dynamic_stack_slot);
-
-                    let mut mflags = MemFlags::new();
-                    // Stack slots are required to be accessible and aligned.
-                    mflags.set_notrap();
-                    mflags.set_aligned();
-                    pos.func.dfg.replace(inst).store(mflags, arg, addr, 0);
+                ir::Opcode::RotrImm => {
+                    replace.rotr(arg, imm);
                 }
-                InstructionData::TableAddr {
-                    opcode: ir::Opcode::TableAddr,
-                    table,
-                    arg,
-                    offset,
-                } => expand_table_addr(isa, inst, &mut pos.func, table, arg, offset),
-
-                InstructionData::BinaryImm64 { opcode, arg, imm } => {
-                    let is_signed = match opcode {
-                        ir::Opcode::IaddImm
-                        | ir::Opcode::IrsubImm
-                        | ir::Opcode::ImulImm
-                        | ir::Opcode::SdivImm
-                        | ir::Opcode::SremImm
-                        | ir::Opcode::IfcmpImm => true,
-                        _ => false,
-                    };
-
-                    let imm = imm_const(&mut pos, arg, imm, is_signed);
-                    let replace = pos.func.dfg.replace(inst);
-                    match opcode {
-                        // bitops
-                        ir::Opcode::BandImm => {
-                            replace.band(arg, imm);
-                        }
-                        ir::Opcode::BorImm => {
-                            replace.bor(arg, imm);
-                        }
-                        ir::Opcode::BxorImm => {
-                            replace.bxor(arg, imm);
-                        }
-                        // bitshifting
-                        ir::Opcode::IshlImm => {
-                            replace.ishl(arg, imm);
-                        }
-                        ir::Opcode::RotlImm => {
-                            replace.rotl(arg, imm);
-                        }
-                        ir::Opcode::RotrImm => {
-                            replace.rotr(arg, imm);
-                        }
-                        ir::Opcode::SshrImm => {
-                            replace.sshr(arg, imm);
-                        }
-                        ir::Opcode::UshrImm => {
-                            replace.ushr(arg, imm);
-                        }
-                        // math
-                        ir::Opcode::IaddImm => {
-                            replace.iadd(arg, imm);
-                        }
-                        ir::Opcode::IrsubImm => {
-                            // note: arg order reversed
-                            replace.isub(imm, arg);
-                        }
-                        ir::Opcode::ImulImm => {
-                            replace.imul(arg, imm);
-                        }
-                        ir::Opcode::SdivImm => {
-                            replace.sdiv(arg, imm);
-                        }
-                        ir::Opcode::SremImm => {
-                            replace.srem(arg, imm);
-                        }
-                        ir::Opcode::UdivImm => {
-                            replace.udiv(arg, imm);
-                        }
-                        ir::Opcode::UremImm => {
-                            replace.urem(arg, imm);
-                        }
-                        // comparisons
-                        ir::Opcode::IfcmpImm => {
-                            replace.ifcmp(arg, imm);
-                        }
-                        _ => prev_pos = pos.position(),
-                    };
+                ir::Opcode::SshrImm => {
+                    replace.sshr(arg, imm);
+                }
+                ir::Opcode::UshrImm => {
+                    replace.ushr(arg, imm);
+                }
+                // math
+                ir::Opcode::IaddImm => {
+                    replace.iadd(arg, imm);
+                }
+                ir::Opcode::IrsubImm => {
+                    // note: arg order reversed
+                    replace.isub(imm, arg);
+                }
+                ir::Opcode::ImulImm => {
+                    replace.imul(arg, imm);
+                }
+                ir::Opcode::SdivImm => {
+                    replace.sdiv(arg, imm);
+                }
+                ir::Opcode::SremImm => {
+                    replace.srem(arg, imm);
+                }
+                ir::Opcode::UdivImm => {
+                    replace.udiv(arg, imm);
+                }
+                ir::Opcode::UremImm => {
+                    replace.urem(arg, imm);
                 }
-
                 // comparisons
-                InstructionData::IntCompareImm {
-                    opcode: ir::Opcode::IcmpImm,
-                    cond,
-                    arg,
-                    imm,
-                } => {
-                    let imm = imm_const(&mut pos, arg, imm, true);
-                    pos.func.dfg.replace(inst).icmp(cond, arg, imm);
+                ir::Opcode::IfcmpImm => {
+                    replace.ifcmp(arg, imm);
                 }
+                _ => return false,
+            };
+        }
 
-                _ => {
-                    prev_pos = pos.position();
-                    continue;
-                }
-            }
+        // saturating arithmetic
+        InstructionData::Binary {
+            opcode:
+                opcode @ (ir::Opcode::UaddSat
+                | ir::Opcode::SaddSat
+                | ir::Opcode::UsubSat
+                | ir::Opcode::SsubSat),
+            args,
+        } if matches!(pos.func.dfg.value_type(args[0]), I8 | I16 | I32 | I64) => {
+            expand_sat_arith(inst, pos, opcode, args[0], args[1]);
+        }
+
+        // carry and borrow arithmetic
+        InstructionData::Binary {
+            opcode: opcode @ (ir::Opcode::IaddCout | ir::Opcode::IsubBout),
+            args,
+        } if expands_carry_arith(isa, pos.func.dfg.value_type(args[0])) => {
+            expand_carry_arith(inst, pos, opcode, args[0], args[1], None);
+        }
+        InstructionData::Ternary {
+            opcode:
+                opcode @ (ir::Opcode::IaddCin
+                | ir::Opcode::IaddCarry
+                | ir::Opcode::IsubBin
+                | ir::Opcode::IsubBorrow),
+            args,
+        } if expands_carry_arith(isa, pos.func.dfg.value_type(args[0])) => {
+            expand_carry_arith(inst, pos, opcode, args[0], args[1], Some(args[2]));
+        }
+
+        // comparisons
+        InstructionData::IntCompareImm {
+            opcode: ir::Opcode::IcmpImm,
+            cond,
+            arg,
+            imm,
+        } => {
+            let imm = imm_const(pos, arg, imm, true);
+            pos.func.dfg.replace(inst).icmp(cond, arg, imm);
+        }
+
+        _ => return false,
+    }
+    true
+}
+
+/// Expand scalar saturating arithmetic into the wrapping operation, an overflow check and a
+/// `select` of the saturated value. Vector saturating arithmetic is left to the backends, which
+/// have native instructions for it.
+fn expand_sat_arith(inst: ir::Inst, pos: &mut FuncCursor, opcode: ir::Opcode, x: Value, y: Value) {
+    let ty = pos.func.dfg.value_type(x);
+    let bits = ty.bits();
+    let umax = u64::MAX >> (64 - bits);
+    let smax = umax >> 1;
+
+    let (result, overflow, saturated) = match opcode {
+        ir::Opcode::UaddSat => {
+            let sum = pos.ins().iadd(x, y);
+            let overflow = pos.ins().icmp(IntCC::UnsignedLessThan, sum, x);
+            let saturated = pos.ins().iconst(ty, umax as i64);
+            (sum, overflow, saturated)
+        }
+        ir::Opcode::UsubSat => {
+            let diff = pos.ins().isub(x, y);
+            let overflow = pos.ins().icmp(IntCC::UnsignedLessThan, x, y);
+            let saturated = pos.ins().iconst(ty, 0);
+            (diff, overflow, saturated)
+        }
+        ir::Opcode::SaddSat | ir::Opcode::SsubSat => {
+            // The operation overflowed if the sign bit is set in both of `a` and `b`.
+            let (result, a, b) = if opcode == ir::Opcode::SaddSat {
+                // The sum has a different sign than both operands.
+                let sum = pos.ins().iadd(x, y);
+                (sum, pos.ins().bxor(sum, x), pos.ins().bxor(sum, y))
+            } else {
+                // The operands have different signs, and the difference doesn't have the sign
+                // of `x`.
+                let diff = pos.ins().isub(x, y);
+                (diff, pos.ins().bxor(x, y), pos.ins().bxor(x, diff))
+            };
+            let both = pos.ins().band(a, b);
+            let overflow = pos.ins().icmp_imm(IntCC::SignedLessThan, both, 0);
+            // An overflow saturates towards the sign of `x`: flipping all bits of the maximum
+            // gives the minimum when `x` is negative.
+            let sign = pos.ins().sshr_imm(x, i64::from(bits - 1));
+            let smax = pos.ins().iconst(ty, smax as i64);
+            let saturated = pos.ins().bxor(sign, smax);
+            (result, overflow, saturated)
+        }
+        _ => unreachable!(),
+    };
+
+    pos.func
+        .dfg
+        .replace(inst)
+        .select(overflow, saturated, result);
+}
+
+/// Whether scalar carry and borrow arithmetic on `ty` needs expanding for `isa`.
+fn expands_carry_arith(isa: &dyn TargetIsa, ty: ir::Type) -> bool {
+    matches!(ty, I8 | I16 | I32 | I64) && !isa.lowers_carry_arithmetic()
+}
 
-            // Legalization implementations require fixpoint loop here.
-            // TODO: fix this.
-            pos.set_position(prev_pos);
+/// Expand carry and borrow arithmetic into the wrapping operation and unsigned comparisons
+/// computing the carry or borrow out. `carry_in` is the carry or borrow in, if the instruction
+/// takes one.
+fn expand_carry_arith(
+    inst: ir::Inst,
+    pos: &mut FuncCursor,
+    opcode: ir::Opcode,
+    x: Value,
+    y: Value,
+    carry_in: Option<Value>,
+) {
+    let ty = pos.func.dfg.value_type(x);
+    let is_add = matches!(
+        opcode,
+        ir::Opcode::IaddCin | ir::Opcode::IaddCout | ir::Opcode::IaddCarry
+    );
+
+    let (result, carry) = if is_add {
+        let sum = pos.ins().iadd(x, y);
+        (sum, pos.ins().icmp(IntCC::UnsignedLessThan, sum, x))
+    } else {
+        let diff = pos.ins().isub(x, y);
+        (diff, pos.ins().icmp(IntCC::UnsignedLessThan, x, y))
+    };
+
+    // Adding or subtracting the carry in can only wrap around if the first step didn't, so the
+    // carry out is set if either step wrapped.
+    let (result, carry) = match carry_in {
+        None => (result, carry),
+        Some(carry_in) => {
+            let carry_in = pos.ins().bint(ty, carry_in);
+            let (result, wrapped) = if is_add {
+                let sum = pos.ins().iadd(result, carry_in);
+                (sum, pos.ins().icmp(IntCC::UnsignedLessThan, sum, result))
+            } else {
+                let diff = pos.ins().isub(result, carry_in);
+                let wrapped = pos.ins().icmp(IntCC::UnsignedLessThan, result, carry_in);
+                (diff, wrapped)
+            };
+            (result, pos.ins().bor(carry, wrapped))
         }
+    };
+
+    let results = pos.func.dfg.inst_results(inst).to_vec();
+    pos.func.dfg.clear_results(inst);
+    pos.func.dfg.change_to_alias(results[0], result);
+    if let Some(&carry_out) = results.get(1) {
+        pos.func.dfg.change_to_alias(carry_out, carry);
     }
+    pos.remove_inst();
 }
 
 /// Custom expansion for conditional trap instructions.
@@ -337,3 +536,65 @@ fn expand_cond_trap(
     cfg.recompute_block(pos.func, new_block_resume);
     cfg.recompute_block(pos.func, new_block_trap);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{types, AbiParam, Function, Signature};
+    use crate::isa::CallConv;
+
+    fn iadd_imm_function() -> Function {
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(types::I32));
+        sig.returns.push(AbiParam::new(types::I32));
+        let mut func = Function::with_name_signature(Default::default(), sig);
+        let block = func.dfg.make_block();
+        let arg = func.dfg.append_block_param(block, types::I32);
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(block);
+        let sum = pos.ins().iadd_imm(arg, 1);
+        pos.ins().return_(&[sum]);
+        func
+    }
+
+    #[test]
+    fn expansions_converge() {
+        let mut func = iadd_imm_function();
+        // Split each `iadd_imm` into an `iconst` and an `iadd`, which are left alone.
+        let result = legalize_with(&mut func, |pos, inst| {
+            let (arg, imm) = match pos.func.dfg[inst] {
+                InstructionData::BinaryImm64 {
+                    opcode: ir::Opcode::IaddImm,
+                    arg,
+                    imm,
+                } => (arg, imm),
+                _ => return false,
+            };
+            let ty = pos.func.dfg.value_type(arg);
+            let imm = pos.ins().iconst(ty, imm);
+            pos.func.dfg.replace(inst).iadd(arg, imm);
+            true
+        });
+        assert!(result.is_ok());
+        let text = func.display().to_string();
+        assert!(!text.contains("iadd_imm"), "{}", text);
+        assert!(text.contains("iadd v0, v2"), "{}", text);
+    }
+
+    #[test]
+    fn expansion_loop() {
+        let mut func = iadd_imm_function();
+        // An expansion which claims to have replaced an instruction without changing it is
+        // handed the same instruction forever.
+        let mut calls = 0;
+        let result = legalize_with(&mut func, |_, _| {
+            calls += 1;
+            true
+        });
+        match result {
+            Err(CodegenError::LegalizerLoop(inst)) => assert_eq!(inst, "v1 = iadd_imm.i32 v0, 1"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(calls, MAX_EXPANSIONS_WITHOUT_PROGRESS);
+    }
+}
@@ -58,7 +58,7 @@ use hashbrown::{hash_map, HashMap, HashSet};
 #[cfg(feature = "std")]
 use std::collections::{hash_map, HashMap, HashSet};
 
-pub use crate::context::Context;
+pub use crate::context::{CompileBudget, Context, IrSize};
 pub use crate::value_label::{ValueLabelsRanges, ValueLocRange};
 pub use crate::verifier::verify_function;
 pub use crate::write::write_function;
@@ -114,7 +114,7 @@ mod value_label;
 #[cfg(feature = "souper-harvest")]
 mod souper_harvest;
 
-pub use crate::result::{CodegenError, CodegenResult, CompileError};
+pub use crate::result::{CodegenError, CodegenErrorKind, CodegenResult, CompileError};
 
 #[cfg(feature = "incremental-cache")]
 pub mod incremental_cache;
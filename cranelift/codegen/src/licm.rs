@@ -3,7 +3,7 @@
 use crate::cursor::{Cursor, FuncCursor};
 use crate::dominator_tree::DominatorTree;
 use crate::entity::{EntityList, ListPool};
-use crate::flowgraph::{BlockPredecessor, ControlFlowGraph};
+use crate::flowgraph::{BlockPredecessor, ControlFlowGraph, DfsTraversal, TraversalEvent};
 use crate::fx::FxHashSet;
 use crate::ir::{
     Block, DataFlowGraph, Function, Inst, InstBuilder, InstructionData, Layout, Opcode, Type, Value,
@@ -217,27 +217,16 @@ fn postorder_blocks_loop(
     cfg: &ControlFlowGraph,
     lp: Loop,
 ) -> Vec<Block> {
-    let mut grey = FxHashSet();
-    let mut black = FxHashSet();
-    let mut stack = vec![loop_analysis.loop_header(lp)];
     let mut postorder = Vec::new();
-
-    while !stack.is_empty() {
-        let node = stack.pop().unwrap();
-        if !grey.contains(&node) {
-            // This is a white node. Mark it as gray.
-            grey.insert(node);
-            stack.push(node);
-            // Get any children we've never seen before.
-            for child in cfg.succ_iter(node) {
-                if loop_analysis.is_in_loop(child, lp) && !grey.contains(&child) {
-                    stack.push(child);
-                }
+    DfsTraversal::new().traverse_from(
+        cfg,
+        loop_analysis.loop_header(lp),
+        |block| loop_analysis.is_in_loop(block, lp),
+        |event, block| {
+            if event == TraversalEvent::Exit {
+                postorder.push(block);
             }
-        } else if !black.contains(&node) {
-            postorder.push(node);
-            black.insert(node);
-        }
-    }
+        },
+    );
     postorder
 }
@@ -95,6 +95,11 @@
 //!   (low address)
 //! ```
 //!
+//! Backends for which `ABIMachineSpec::uses_outgoing_args_area` returns true
+//! instead reserve space for the args of the largest call in the prologue,
+//! below nominal SP, and store stack args there without moving SP at the
+//! callsite.
+//!
 //! # Multi-value Returns
 //!
 //! We support multi-value returns by using multiple return-value
@@ -108,10 +113,10 @@ use crate::ir::types::*;
 use crate::ir::{ArgumentExtension, ArgumentPurpose, DynamicStackSlot, Signature, StackSlot};
 use crate::isa::TargetIsa;
 use crate::settings;
-use crate::settings::ProbestackStrategy;
-use crate::CodegenResult;
+use crate::settings::{CodeModel, ProbestackStrategy};
 use crate::{ir, isa};
 use crate::{machinst::*, trace};
+use crate::{CodegenError, CodegenResult};
 use alloc::vec::Vec;
 use regalloc2::{PReg, PRegSet};
 use smallvec::{smallvec, SmallVec};
@@ -353,6 +358,19 @@ pub trait ABIMachineSpec {
         add_ret_area_ptr: bool,
     ) -> CodegenResult<(ABIArgVec, i64, Option<usize>)>;
 
+    /// Check that calls to variadic functions are supported with `call_conv`, and return the
+    /// hidden argument such a call needs, if any: a register and the value to put in it. `args`
+    /// are the locations of the call's arguments, computed by `compute_arg_locs`.
+    fn variadic_call_arg(
+        call_conv: isa::CallConv,
+        _args: &[ABIArg],
+    ) -> CodegenResult<Option<(PReg, u64)>> {
+        Err(CodegenError::Unsupported(format!(
+            "variadic calls with the {} calling convention",
+            call_conv
+        )))
+    }
+
     /// Returns the offset from FP to the argument area, i.e., jumping over the saved FP, return
     /// address, and maybe other standard elements depending on ABI (e.g. Wasm TLS reg).
     fn fp_to_arg_offset(call_conv: isa::CallConv, flags: &settings::Flags) -> i64;
@@ -427,6 +445,15 @@ pub trait ABIMachineSpec {
     /// Generate a meta-instruction that adjusts the nominal SP offset.
     fn gen_nominal_sp_adj(amount: i32) -> Self::I;
 
+    /// Whether call sites store stack arguments and return values into a
+    /// fixed outgoing-argument area at the bottom of the frame, rather than
+    /// adjusting SP around each call. The area is sized by the largest call
+    /// in the function (see `Callee::accumulate_outgoing_args_size`), and
+    /// `gen_clobber_save` must allocate it below nominal SP.
+    fn uses_outgoing_args_area() -> bool {
+        false
+    }
+
     /// Generates the mandatory part of the prologue, irrespective of whether
     /// the usual frame-setup sequence for this architecture is required or not,
     /// e.g. extra unwind instructions.
@@ -449,7 +476,7 @@ pub trait ABIMachineSpec {
     fn gen_epilogue_frame_restore(flags: &settings::Flags) -> SmallInstVec<Self::I>;
 
     /// Generate a probestack call.
-    fn gen_probestack(_frame_size: u32) -> SmallInstVec<Self::I>;
+    fn gen_probestack(flags: &settings::Flags, frame_size: u32) -> SmallInstVec<Self::I>;
 
     /// Generate a inline stack probe.
     fn gen_inline_probestack(_frame_size: u32, _guard_size: u32) -> SmallInstVec<Self::I>;
@@ -520,6 +547,7 @@ pub trait ABIMachineSpec {
     /// temporaries to generate the call (for the size immediate and
     /// possibly for the address of `memcpy` itself).
     fn gen_memcpy(
+        flags: &settings::Flags,
         call_conv: isa::CallConv,
         dst: Reg,
         src: Reg,
@@ -542,12 +570,14 @@ pub trait ABIMachineSpec {
     fn get_regs_clobbered_by_call(call_conv_of_callee: isa::CallConv) -> PRegSet;
 
     /// Get the needed extension mode, given the mode attached to the argument
-    /// in the signature and the calling convention. The input (the attribute in
-    /// the signature) specifies what extension type should be done *if* the ABI
-    /// requires extension to the full register; this method's return value
-    /// indicates whether the extension actually *will* be done.
+    /// in the signature, the argument's type and the calling convention. The
+    /// input (the attribute in the signature) specifies what extension type
+    /// should be done *if* the ABI requires extension to the full register;
+    /// this method's return value indicates whether the extension actually
+    /// *will* be done, and how.
     fn get_ext_mode(
         call_conv: isa::CallConv,
+        ty: Type,
         specified: ir::ArgumentExtension,
     ) -> ir::ArgumentExtension;
 }
@@ -577,6 +607,8 @@ pub struct SigData {
     stack_ret_arg: Option<usize>,
     /// Calling convention used.
     call_conv: isa::CallConv,
+    /// For variadic signatures, the register and value of the hidden argument that calls pass.
+    variadic_arg: Option<(PReg, u64)>,
 }
 
 impl SigData {
@@ -603,6 +635,10 @@ impl SigData {
             ArgsOrRets::Args,
             need_stack_return_area,
         )?;
+        let variadic_arg = match sig.variadic {
+            Some(_) => M::variadic_call_arg(sig.call_conv, &args)?,
+            None => None,
+        };
 
         trace!(
             "ABISig: sig {:?} => args = {:?} rets = {:?} arg stack = {} ret stack = {} stack_ret_arg = {:?}",
@@ -621,6 +657,7 @@ impl SigData {
             sized_stack_ret_space,
             stack_ret_arg,
             call_conv: sig.call_conv,
+            variadic_arg,
         })
     }
 
@@ -1359,7 +1396,7 @@ impl<M: ABIMachineSpec> Callee<M> {
                 } => {
                     // However, we have to respect the extention mode for stack
                     // slots, or else we grab the wrong bytes on big-endian.
-                    let ext = M::get_ext_mode(sigs[self.sig].call_conv, extension);
+                    let ext = M::get_ext_mode(sigs[self.sig].call_conv, ty, extension);
                     let ty = match (ext, ty_bits(ty) as u32) {
                         (ArgumentExtension::Uext, n) | (ArgumentExtension::Sext, n)
                             if n < M::word_bits() =>
@@ -1457,7 +1494,7 @@ impl<M: ABIMachineSpec> Callee<M> {
                             reg, ty, extension, ..
                         } => {
                             let from_bits = ty_bits(ty) as u8;
-                            let ext = M::get_ext_mode(sigs[self.sig].call_conv, extension);
+                            let ext = M::get_ext_mode(sigs[self.sig].call_conv, ty, extension);
                             let reg: Writable<Reg> = Writable::from_reg(Reg::from(reg));
                             match (ext, from_bits) {
                                 (ArgumentExtension::Uext, n) | (ArgumentExtension::Sext, n)
@@ -1491,7 +1528,7 @@ impl<M: ABIMachineSpec> Callee<M> {
                             let off = i32::try_from(offset).expect(
                                 "Argument stack offset greater than 2GB; should hit impl limit first",
                                 );
-                            let ext = M::get_ext_mode(sigs[self.sig].call_conv, extension);
+                            let ext = M::get_ext_mode(sigs[self.sig].call_conv, ty, extension);
                             // Trash the from_reg; it should be its last use.
                             match (ext, from_bits) {
                                 (ArgumentExtension::Uext, n) | (ArgumentExtension::Sext, n)
@@ -1757,7 +1794,7 @@ impl<M: ABIMachineSpec> Callee<M> {
                         let guard_size = 1 << self.flags.probestack_size_log2();
                         M::gen_inline_probestack(total_stacksize, guard_size)
                     } else {
-                        M::gen_probestack(total_stacksize)
+                        M::gen_probestack(&self.flags, total_stacksize)
                     },
                 );
             }
@@ -1933,6 +1970,15 @@ pub enum CallDest {
     Reg(Reg),
 }
 
+/// The distance to assume for the libcalls that the ABI code emits itself, such as probestack
+/// and memcpy. These are near unless the large code model is in use.
+pub(crate) fn abi_libcall_distance(flags: &settings::Flags) -> RelocDistance {
+    match flags.code_model() {
+        CodeModel::Small | CodeModel::Medium => RelocDistance::Near,
+        CodeModel::Large => RelocDistance::Far,
+    }
+}
+
 impl<M: ABIMachineSpec> Caller<M> {
     /// Create a callsite ABI object for a call directly to the specified function.
     pub fn from_func(
@@ -2032,14 +2078,24 @@ impl<M: ABIMachineSpec> Caller<M> {
     }
 
     /// Emit code to pre-adjust the stack, prior to argument copies and call.
+    ///
+    /// If the backend uses an outgoing-argument area, this only makes sure
+    /// the area is large enough for this call.
     pub fn emit_stack_pre_adjust(&self, ctx: &mut Lower<M::I>) {
         let off =
             ctx.sigs()[self.sig].sized_stack_arg_space + ctx.sigs()[self.sig].sized_stack_ret_space;
-        adjust_stack_and_nominal_sp::<M>(ctx, off as i32, /* is_sub = */ true)
+        if M::uses_outgoing_args_area() {
+            ctx.abi_mut().accumulate_outgoing_args_size(off as u32);
+        } else {
+            adjust_stack_and_nominal_sp::<M>(ctx, off as i32, /* is_sub = */ true)
+        }
     }
 
     /// Emit code to post-adjust the satck, after call return and return-value copies.
     pub fn emit_stack_post_adjust(&self, ctx: &mut Lower<M::I>) {
+        if M::uses_outgoing_args_area() {
+            return;
+        }
         let off =
             ctx.sigs()[self.sig].sized_stack_arg_space + ctx.sigs()[self.sig].sized_stack_ret_space;
         adjust_stack_and_nominal_sp::<M>(ctx, off as i32, /* is_sub = */ false)
@@ -2075,6 +2131,7 @@ impl<M: ABIMachineSpec> Caller<M> {
                 let tmp1 = ctx.alloc_tmp(M::word_type()).only_reg().unwrap();
                 let tmp2 = ctx.alloc_tmp(M::word_type()).only_reg().unwrap();
                 for insn in M::gen_memcpy(
+                    &self.flags,
                     memcpy_call_conv,
                     dst_ptr.to_reg(),
                     src_ptr,
@@ -2141,7 +2198,8 @@ impl<M: ABIMachineSpec> Caller<M> {
                         &ABIArgSlot::Reg {
                             reg, ty, extension, ..
                         } => {
-                            let ext = M::get_ext_mode(ctx.sigs()[self.sig].call_conv, extension);
+                            let ext =
+                                M::get_ext_mode(ctx.sigs()[self.sig].call_conv, ty, extension);
                             if ext != ir::ArgumentExtension::None && ty_bits(ty) < word_bits {
                                 assert_eq!(word_rc, reg.class());
                                 let signed = match ext {
@@ -2187,7 +2245,8 @@ impl<M: ABIMachineSpec> Caller<M> {
                             extension,
                             ..
                         } => {
-                            let ext = M::get_ext_mode(ctx.sigs()[self.sig].call_conv, extension);
+                            let ext =
+                                M::get_ext_mode(ctx.sigs()[self.sig].call_conv, ty, extension);
                             let (data, ty) =
                                 if ext != ir::ArgumentExtension::None && ty_bits(ty) < word_bits {
                                     assert_eq!(word_rc, from_reg.class());
@@ -2297,6 +2356,20 @@ impl<M: ABIMachineSpec> Caller<M> {
             }
         }
 
+        if let Some((preg, value)) = ctx.sigs()[self.sig].variadic_arg {
+            let rd = ctx.alloc_tmp(word_type).only_reg().unwrap();
+            let insts = M::I::gen_constant(ValueRegs::one(rd), value.into(), word_type, |ty| {
+                ctx.alloc_tmp(ty).only_reg().unwrap()
+            });
+            for inst in insts {
+                ctx.emit(inst);
+            }
+            self.uses.push(CallArgPair {
+                vreg: rd.to_reg(),
+                preg: preg.into(),
+            });
+        }
+
         let (uses, defs) = (
             mem::replace(&mut self.uses, Default::default()),
             mem::replace(&mut self.defs, Default::default()),
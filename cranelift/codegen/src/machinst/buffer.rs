@@ -232,6 +232,9 @@ pub struct MachBuffer<I: VCodeInst> {
     label_aliases: SmallVec<[MachLabel; 16]>,
     /// Constants that must be emitted at some point.
     pending_constants: SmallVec<[MachLabelConstant; 16]>,
+    /// Number of bytes of constant data emitted so far, including the
+    /// alignment padding in front of each constant.
+    rodata_size: CodeOffset,
     /// Fixups that must be performed after all code is emitted.
     fixup_records: SmallVec<[MachLabelFixup<I>; 16]>,
     /// Current deadline at which all constants are flushed and all code labels
@@ -270,6 +273,7 @@ impl MachBufferFinalized<Stencil> {
     pub(crate) fn apply_params(self, params: &FunctionParameters) -> MachBufferFinalized<Final> {
         MachBufferFinalized {
             data: self.data,
+            rodata_size: self.rodata_size,
             relocs: self.relocs,
             traps: self.traps,
             call_sites: self.call_sites,
@@ -291,6 +295,9 @@ impl MachBufferFinalized<Stencil> {
 pub struct MachBufferFinalized<T: CompilePhase> {
     /// The buffer contents, as raw bytes.
     pub(crate) data: SmallVec<[u8; 1024]>,
+    /// Number of bytes in `data` holding constants (including their
+    /// alignment padding) rather than code.
+    pub(crate) rodata_size: CodeOffset,
     /// Any relocations referring to this code. Note that only *external*
     /// relocations are tracked here; references to labels within the buffer are
     /// resolved before emission.
@@ -373,6 +380,7 @@ impl<I: VCodeInst> MachBuffer<I> {
             label_offsets: SmallVec::new(),
             label_aliases: SmallVec::new(),
             pending_constants: SmallVec::new(),
+            rodata_size: 0,
             fixup_records: SmallVec::new(),
             island_deadline: UNKNOWN_LABEL_OFFSET,
             island_worst_case_size: 0,
@@ -1136,9 +1144,11 @@ impl<I: VCodeInst> MachBuffer<I> {
         // First flush out all constants so we have more labels in case fixups
         // are applied against these labels.
         for MachLabelConstant { label, align, data } in mem::take(&mut self.pending_constants) {
+            let start = self.cur_offset();
             self.align_to(align);
             self.bind_label(label);
             self.put_data(&data[..]);
+            self.rodata_size += self.cur_offset() - start;
         }
 
         for fixup in mem::take(&mut self.fixup_records) {
@@ -1286,6 +1296,7 @@ impl<I: VCodeInst> MachBuffer<I> {
 
         MachBufferFinalized {
             data: self.data,
+            rodata_size: self.rodata_size,
             relocs: self.relocs,
             traps: self.traps,
             call_sites: self.call_sites,
@@ -1297,6 +1308,23 @@ impl<I: VCodeInst> MachBuffer<I> {
 
     /// Add an external relocation at the current offset.
     pub fn add_reloc(&mut self, kind: Reloc, name: &ExternalName, addend: Addend) {
+        self.add_reloc_at_offset(self.cur_offset(), kind, name, addend);
+    }
+
+    /// Add an external relocation at the given offset.
+    ///
+    /// This is useful when the field to be patched lies in the middle of an
+    /// instruction or sequence that is emitted as a whole, e.g. a call or
+    /// symbol-address sequence referring to another function or an imported
+    /// symbol by name. The offset must not lie beyond the current end of the
+    /// buffer plus the size of the code about to be emitted.
+    pub fn add_reloc_at_offset(
+        &mut self,
+        offset: CodeOffset,
+        kind: Reloc,
+        name: &ExternalName,
+        addend: Addend,
+    ) {
         let name = name.clone();
         // FIXME(#3277): This should use `I::LabelUse::from_reloc` to optionally
         // generate a label-use statement to track whether an island is possibly
@@ -1332,7 +1360,7 @@ impl<I: VCodeInst> MachBuffer<I> {
         // when a relocation can't otherwise be resolved later, so it shouldn't
         // actually result in any memory unsafety or anything like that.
         self.relocs.push(MachReloc {
-            offset: self.data.len() as CodeOffset,
+            offset,
             kind,
             name,
             addend,
@@ -1340,6 +1368,9 @@ impl<I: VCodeInst> MachBuffer<I> {
     }
 
     /// Add a trap record at the current offset.
+    ///
+    /// Trap records are always added in increasing offset order, since the
+    /// buffer only ever grows at its tail.
     pub fn add_trap(&mut self, code: TrapCode) {
         self.traps.push(MachTrap {
             offset: self.data.len() as CodeOffset,
@@ -1423,6 +1454,13 @@ impl<T: CompilePhase> MachBufferFinalized<T> {
         self.data.len() as CodeOffset
     }
 
+    /// Get the number of bytes taken up by constants, including their
+    /// alignment padding. Constants are placed in islands between code or
+    /// after the end of the code, so this is a part of `total_size()`.
+    pub fn rodata_size(&self) -> CodeOffset {
+        self.rodata_size
+    }
+
     /// Return the code in this mach buffer as a hex string for testing purposes.
     pub fn stringify_code_bytes(&self) -> String {
         // This is pretty lame, but whatever ..
@@ -1460,6 +1498,20 @@ impl<T: CompilePhase> MachBufferFinalized<T> {
         &self.traps[..]
     }
 
+    /// Look up the trap code recorded for the instruction at `offset`, if any.
+    ///
+    /// Trap records are kept sorted by offset, so this can be used, e.g. by a
+    /// signal handler, to map a faulting PC back to the trap it represents.
+    /// When several trap codes are recorded at the same offset, the first one
+    /// is returned.
+    pub fn lookup_trap(&self, offset: CodeOffset) -> Option<TrapCode> {
+        let idx = self.traps.partition_point(|trap| trap.offset < offset);
+        self.traps
+            .get(idx)
+            .filter(|trap| trap.offset == offset)
+            .map(|trap| trap.code)
+    }
+
     /// Get the stack map metadata for this code.
     pub fn stack_maps(&self) -> &[MachStackMap] {
         &self.stack_maps[..]
@@ -2048,6 +2100,22 @@ mod test {
         assert_eq!(&golden_data[..], &buf.data[..]);
     }
 
+    #[test]
+    fn rodata_size() {
+        let mut buf = MachBuffer::<Inst>::new();
+        buf.reserve_labels_for_blocks(1);
+        buf.bind_label(label(0));
+        buf.put4(0xd503201f);
+
+        let constant = buf.get_label();
+        buf.defer_constant(constant, 8, &[1, 2, 3, 4, 5, 6, 7, 8], u32::MAX);
+
+        let buf = buf.finish();
+        assert_eq!(buf.total_size(), 16);
+        assert_eq!(buf.rodata_size(), 12);
+        assert_eq!(&buf.data()[8..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
     #[test]
     fn metadata_records() {
         let mut buf = MachBuffer::<Inst>::new();
@@ -2073,10 +2141,17 @@ mod test {
             1,
         );
         buf.put1(4);
+        buf.add_reloc_at_offset(
+            buf.cur_offset() + 2,
+            Reloc::X86PCRel4,
+            &ExternalName::User(UserExternalNameRef::new(2)),
+            -4,
+        );
+        buf.put_data(&[5, 6, 7, 8, 9, 10]);
 
         let buf = buf.finish();
 
-        assert_eq!(buf.data(), &[1, 2, 3, 4]);
+        assert_eq!(buf.data(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
         assert_eq!(
             buf.traps()
                 .iter()
@@ -2088,6 +2163,10 @@ mod test {
                 (2, TrapCode::IntegerDivisionByZero)
             ]
         );
+        assert_eq!(buf.lookup_trap(0), None);
+        assert_eq!(buf.lookup_trap(1), Some(TrapCode::HeapOutOfBounds));
+        assert_eq!(buf.lookup_trap(2), Some(TrapCode::IntegerOverflow));
+        assert_eq!(buf.lookup_trap(3), None);
         assert_eq!(
             buf.call_sites()
                 .iter()
@@ -2100,7 +2179,7 @@ mod test {
                 .iter()
                 .map(|reloc| (reloc.offset, reloc.kind))
                 .collect::<Vec<_>>(),
-            vec![(2, Reloc::Abs4), (3, Reloc::Abs8)]
+            vec![(2, Reloc::Abs4), (3, Reloc::Abs8), (6, Reloc::X86PCRel4)]
         );
     }
 }
@@ -16,7 +16,7 @@ pub use crate::machinst::{
     ABIArg, ABIArgSlot, InputSourceInst, Lower, RealReg, Reg, RelocDistance, Sig, VCodeInst,
     Writable,
 };
-pub use crate::settings::TlsModel;
+pub use crate::settings::{CodeModel, TlsModel};
 
 pub type Unit = ();
 pub type ValueSlice = (ValueList, usize);
@@ -757,11 +757,11 @@ macro_rules! isle_prelude_methods {
         #[inline]
         fn func_ref_data(&mut self, func_ref: FuncRef) -> (SigRef, ExternalName, RelocDistance) {
             let funcdata = &self.lower_ctx.dfg().ext_funcs[func_ref];
-            (
-                funcdata.signature,
-                funcdata.name.clone(),
-                funcdata.reloc_distance(),
-            )
+            let dist = match self.flags.code_model() {
+                CodeModel::Small | CodeModel::Medium => funcdata.reloc_distance(),
+                CodeModel::Large => RelocDistance::Far,
+            };
+            (funcdata.signature, funcdata.name.clone(), dist)
         }
 
         #[inline]
@@ -775,6 +775,10 @@ macro_rules! isle_prelude_methods {
             global_value: GlobalValue,
         ) -> Option<(ExternalName, RelocDistance, i64)> {
             let (name, reloc, offset) = self.lower_ctx.symbol_value_data(global_value)?;
+            let reloc = match self.flags.code_model() {
+                CodeModel::Small => reloc,
+                CodeModel::Medium | CodeModel::Large => RelocDistance::Far,
+            };
             Some((name.clone(), reloc, offset))
         }
 
@@ -20,6 +20,7 @@ use crate::machinst::{
     VCodeConstants, VCodeInst, ValueRegs, Writable,
 };
 use crate::{trace, CodegenError, CodegenResult};
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use regalloc2::VReg;
 use smallvec::{smallvec, SmallVec};
@@ -27,6 +28,10 @@ use std::fmt::Debug;
 
 use super::{first_user_vreg_index, VCodeBuildDirection};
 
+/// The maximum number of value-label aliases followed when looking up the labels
+/// of a value.
+const MAX_VALUE_LABEL_ALIAS_DEPTH: usize = 10;
+
 /// An "instruction color" partitions CLIF instructions by side-effecting ops.
 /// All instructions with the same "color" are guaranteed not to be separated by
 /// any side-effecting op (for this purpose, loads are also considered
@@ -776,25 +781,25 @@ impl<'func, I: VCodeInst> Lower<'func, I> {
         Ok(())
     }
 
-    fn get_value_labels<'a>(&'a self, val: Value, depth: usize) -> Option<&'a [ValueLabelStart]> {
-        if let Some(ref values_labels) = self.f.dfg.values_labels {
+    fn get_value_labels(&self, mut val: Value) -> Option<&[ValueLabelStart]> {
+        let values_labels = self.f.dfg.values_labels.as_ref()?;
+        // Follow label aliases with a bounded loop rather than recursion so a long
+        // (or cyclic) alias chain can't run away.
+        for _ in 0..=MAX_VALUE_LABEL_ALIAS_DEPTH {
             trace!(
                 "get_value_labels: val {} -> {} -> {:?}",
                 val,
                 self.f.dfg.resolve_aliases(val),
                 values_labels.get(&self.f.dfg.resolve_aliases(val))
             );
-            let val = self.f.dfg.resolve_aliases(val);
+            val = self.f.dfg.resolve_aliases(val);
             match values_labels.get(&val) {
-                Some(&ValueLabelAssignments::Starts(ref list)) => Some(&list[..]),
-                Some(&ValueLabelAssignments::Alias { value, .. }) if depth < 10 => {
-                    self.get_value_labels(value, depth + 1)
-                }
-                _ => None,
+                Some(ValueLabelAssignments::Starts(list)) => return Some(&list[..]),
+                Some(&ValueLabelAssignments::Alias { value, .. }) => val = value,
+                None => return None,
             }
-        } else {
-            None
         }
+        None
     }
 
     fn emit_value_label_marks_for_value(&mut self, val: Value) {
@@ -804,7 +809,7 @@ impl<'func, I: VCodeInst> Lower<'func, I> {
         }
         let reg = regs.only_reg().unwrap();
 
-        if let Some(label_starts) = self.get_value_labels(val, 0) {
+        if let Some(label_starts) = self.get_value_labels(val) {
             let labels = label_starts
                 .iter()
                 .map(|&ValueLabelStart { label, .. }| label)
@@ -1056,6 +1061,16 @@ impl<'func, I: VCodeInst> Lower<'func, I> {
     pub fn retval(&self, idx: usize) -> ValueRegs<Writable<Reg>> {
         writable_value_regs(self.retval_regs[idx])
     }
+
+    /// Build the error to report when the backend has no lowering for `ir_inst`.
+    pub fn no_lowering(&self, ir_inst: Inst) -> CodegenError {
+        let dfg = &self.f.dfg;
+        CodegenError::NoLowering {
+            inst: dfg.display_inst(ir_inst).to_string(),
+            opcode: dfg[ir_inst].opcode(),
+            ctrl_type: dfg.ctrl_typevar(ir_inst),
+        }
+    }
 }
 
 /// Instruction input/output queries.
@@ -60,6 +60,17 @@ use std::string::String;
 #[cfg(feature = "enable-serde")]
 use serde::{Deserialize, Serialize};
 
+/// Assert that the hardware encoding of `$reg` fits in an instruction's register field, as
+/// checked by `$fits`, instead of silently emitting some other register. Like `debug_assert!`,
+/// this is only checked in debug builds, unless the `strict-reg-encoding` feature is enabled.
+macro_rules! assert_reg_field_fits {
+    ($fits:expr, $reg:expr) => {
+        if cfg!(any(debug_assertions, feature = "strict-reg-encoding")) {
+            assert!($fits, "{:?} does not fit in a register field", $reg);
+        }
+    };
+}
+
 #[macro_use]
 pub mod isle;
 
@@ -161,6 +172,13 @@ pub trait MachInst: Clone + Debug {
         offset
     }
 
+    /// The preferred alignment of a block that is the target of a backward
+    /// branch, usually a loop header, when the `align_loops` setting is
+    /// enabled. By default, no alignment occurs.
+    fn loop_header_alignment() -> CodeOffset {
+        1
+    }
+
     /// What is the worst-case instruction size emitted by this instruction type?
     fn worst_case_size() -> CodeOffset;
 
@@ -340,6 +358,7 @@ impl<T: CompilePhase> CompiledCodeBase<T> {
     pub fn code_info(&self) -> CodeInfo {
         CodeInfo {
             total_size: self.buffer.total_size(),
+            rodata_size: self.buffer.rodata_size(),
         }
     }
 
@@ -347,6 +366,32 @@ impl<T: CompilePhase> CompiledCodeBase<T> {
     pub fn code_buffer(&self) -> &[u8] {
         self.buffer.data()
     }
+
+    /// Copies the machine code for this function compilation into `mem`, such as memory
+    /// that a JIT has allocated for executable code.
+    ///
+    /// The code is not relocated. Instead, each relocation is passed to `reloc_sink` and each
+    /// trap site to `trap_sink`, in code offset order, for the caller to apply or record.
+    ///
+    /// # Safety
+    ///
+    /// `mem` must be valid for writes of `self.code_info().total_size` bytes and must not
+    /// overlap the code buffer.
+    pub unsafe fn emit_to_memory(
+        &self,
+        mem: *mut u8,
+        mut reloc_sink: impl FnMut(&MachReloc),
+        mut trap_sink: impl FnMut(&MachTrap),
+    ) {
+        let data = self.buffer.data();
+        core::ptr::copy_nonoverlapping(data.as_ptr(), mem, data.len());
+        for reloc in self.buffer.relocs() {
+            reloc_sink(reloc);
+        }
+        for trap in self.buffer.traps() {
+            trap_sink(trap);
+        }
+    }
 }
 
 /// Result of compiling a `FunctionStencil`, before applying `FunctionParameters` onto it.
@@ -771,6 +771,7 @@ impl<I: VCodeInst> VCode<I> {
         regalloc: &regalloc2::Output,
         want_disasm: bool,
         want_metadata: bool,
+        align_loops: bool,
     ) -> EmitResult<I>
     where
         I: VCodeInst,
@@ -800,6 +801,35 @@ impl<I: VCodeInst> VCode<I> {
         }
         final_order.extend(cold_blocks.clone());
 
+        // Find the loop headers to align, if requested: blocks that are the
+        // target of a branch from a block at or after them in the final
+        // order. Cold blocks are skipped, as they are placed at the end and
+        // aren't worth padding for.
+        let loop_header_alignment = if align_loops {
+            I::loop_header_alignment()
+        } else {
+            1
+        };
+        debug_assert!(loop_header_alignment.is_power_of_two());
+        let mut loop_headers = vec![false; self.num_blocks()];
+        if loop_header_alignment > 1 {
+            let mut order_pos = vec![0; self.num_blocks()];
+            for (pos, &block) in final_order.iter().enumerate() {
+                order_pos[block.index()] = pos;
+            }
+            for (pos, &block) in final_order.iter().enumerate() {
+                if self.block_order.is_cold(block) {
+                    continue;
+                }
+                let (start, end) = self.block_succ_range[block.index()];
+                for &succ in &self.block_succs_preds[start as usize..end as usize] {
+                    if !self.block_order.is_cold(succ) && order_pos[succ.index()] <= pos {
+                        loop_headers[succ.index()] = true;
+                    }
+                }
+            }
+        }
+
         // Compute/save info we need for the prologue: clobbers and
         // number of spillslots.
         //
@@ -849,7 +879,11 @@ impl<I: VCodeInst> VCode<I> {
 
         for (block_order_idx, &block) in final_order.iter().enumerate() {
             trace!("emitting block {:?}", block);
-            let new_offset = I::align_basic_block(buffer.cur_offset());
+            let mut new_offset = I::align_basic_block(buffer.cur_offset());
+            if loop_headers[block.index()] {
+                new_offset =
+                    (new_offset + loop_header_alignment - 1) & !(loop_header_alignment - 1);
+            }
             while new_offset > buffer.cur_offset() {
                 // Pad with NOPs up to the aligned block offset.
                 let nop = I::gen_nop((new_offset - buffer.cur_offset()) as usize);
@@ -1059,8 +1093,11 @@ impl<I: VCodeInst> VCode<I> {
                 let next_block_size =
                     (next_block_range.1.index() - next_block_range.0.index()) as u32;
                 let next_block_ra_insertions = ra_edits_per_block[next_block.index()];
-                let worst_case_next_bb =
+                let mut worst_case_next_bb =
                     I::worst_case_size() * (next_block_size + next_block_ra_insertions);
+                if loop_headers[next_block.index()] {
+                    worst_case_next_bb += loop_header_alignment - 1;
+                }
                 if buffer.island_needed(worst_case_next_bb) {
                     buffer.emit_island(worst_case_next_bb);
                 }
@@ -2,7 +2,8 @@
 
 use regalloc2::checker::CheckerErrors;
 
-use crate::{ir::Function, verifier::VerifierErrors};
+use crate::ir::{types, Function, Opcode, Type};
+use crate::verifier::VerifierErrors;
 use std::string::String;
 
 /// A compilation error.
@@ -35,17 +36,77 @@ pub enum CodegenError {
     /// unsupported by a given target backend.
     Unsupported(String),
 
+    /// The target backend has no lowering for an instruction.
+    ///
+    /// The backend doesn't implement this instruction, or doesn't implement it for this
+    /// controlling type or with the ISA extensions that are currently enabled.
+    NoLowering {
+        /// The instruction, as it is printed in the function.
+        inst: String,
+        /// The instruction's opcode.
+        opcode: Opcode,
+        /// The instruction's controlling type, or `INVALID` if it isn't polymorphic.
+        ctrl_type: Type,
+    },
+
     /// A failure to map Cranelift register representation to a DWARF register representation.
     #[cfg(feature = "unwind")]
     RegisterMappingError(crate::isa::unwind::systemv::RegisterMappingError),
 
     /// Register allocator internal error discovered by the symbolic checker.
     Regalloc(CheckerErrors),
+
+    /// The legalizer kept expanding an instruction without making progress.
+    ///
+    /// This always represents a bug in Cranelift: an expansion re-creates an instruction that has
+    /// to be expanded again. The string is the instruction that was being expanded.
+    LegalizerLoop(String),
+
+    /// A limit configured in the compilation context's `CompileBudget` was exceeded.
+    ///
+    /// The string names the exhausted budget. Unlike `ImplLimitExceeded`, this is not a
+    /// limitation of Cranelift itself but a policy chosen by the embedder.
+    BudgetExceeded(&'static str),
 }
 
 /// A convenient alias for a `Result` that uses `CodegenError` as the error type.
 pub type CodegenResult<T> = Result<T, CodegenError>;
 
+/// A coarse classification of a [`CodegenError`], for embedders that need to react to errors
+/// programmatically rather than by displaying them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CodegenErrorKind {
+    /// The function failed IR verification. This is usually caused by the code that produced the
+    /// IR, but can also be a Cranelift bug if verification runs after optimization passes.
+    InvalidIr,
+    /// The function uses something the target or the current settings don't support.
+    Unsupported,
+    /// The function exceeded a limit, either one of Cranelift's or one set by the embedder.
+    LimitExceeded,
+    /// An internal consistency check failed. This always represents a bug in Cranelift.
+    Internal,
+}
+
+impl CodegenError {
+    /// Classify this error.
+    pub fn kind(&self) -> CodegenErrorKind {
+        match self {
+            CodegenError::Verifier(..) => CodegenErrorKind::InvalidIr,
+            CodegenError::Unsupported(..) | CodegenError::NoLowering { .. } => {
+                CodegenErrorKind::Unsupported
+            }
+            CodegenError::ImplLimitExceeded
+            | CodegenError::CodeTooLarge
+            | CodegenError::BudgetExceeded(..) => CodegenErrorKind::LimitExceeded,
+            #[cfg(feature = "unwind")]
+            CodegenError::RegisterMappingError(..) => CodegenErrorKind::Internal,
+            CodegenError::Regalloc(..) | CodegenError::LegalizerLoop(..) => {
+                CodegenErrorKind::Internal
+            }
+        }
+    }
+}
+
 // This is manually implementing Error and Display instead of using thiserror to reduce the amount
 // of dependencies used by Cranelift.
 impl std::error::Error for CodegenError {
@@ -54,10 +115,13 @@ impl std::error::Error for CodegenError {
             CodegenError::Verifier(source) => Some(source),
             CodegenError::ImplLimitExceeded { .. }
             | CodegenError::CodeTooLarge { .. }
-            | CodegenError::Unsupported { .. } => None,
+            | CodegenError::Unsupported { .. }
+            | CodegenError::NoLowering { .. } => None,
             #[cfg(feature = "unwind")]
             CodegenError::RegisterMappingError { .. } => None,
             CodegenError::Regalloc(..) => None,
+            CodegenError::LegalizerLoop(..) => None,
+            CodegenError::BudgetExceeded(..) => None,
         }
     }
 }
@@ -69,9 +133,26 @@ impl std::fmt::Display for CodegenError {
             CodegenError::ImplLimitExceeded => write!(f, "Implementation limit exceeded"),
             CodegenError::CodeTooLarge => write!(f, "Code for function is too large"),
             CodegenError::Unsupported(feature) => write!(f, "Unsupported feature: {}", feature),
+            CodegenError::NoLowering {
+                inst,
+                opcode,
+                ctrl_type,
+            } => {
+                write!(f, "No lowering for `{}` (opcode `{}`", inst, opcode)?;
+                if *ctrl_type != types::INVALID {
+                    write!(f, ", controlling type `{}`", ctrl_type)?;
+                }
+                write!(f, ")")
+            }
             #[cfg(feature = "unwind")]
             CodegenError::RegisterMappingError(_0) => write!(f, "Register mapping error"),
             CodegenError::Regalloc(errors) => write!(f, "Regalloc validation errors: {:?}", errors),
+            CodegenError::LegalizerLoop(inst) => {
+                write!(f, "Legalization of `{}` did not converge", inst)
+            }
+            CodegenError::BudgetExceeded(budget) => {
+                write!(f, "Compilation budget exceeded: {}", budget)
+            }
         }
     }
 }
@@ -100,3 +181,53 @@ impl<'a> core::fmt::Debug for CompileError<'a> {
 
 /// A convenient alias for a `Result` that uses `CompileError` as the error type.
 pub type CompileResult<'a, T> = Result<T, CompileError<'a>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_kinds() {
+        let errors = [
+            (
+                CodegenError::Verifier(VerifierErrors::default()),
+                CodegenErrorKind::InvalidIr,
+            ),
+            (
+                CodegenError::ImplLimitExceeded,
+                CodegenErrorKind::LimitExceeded,
+            ),
+            (CodegenError::CodeTooLarge, CodegenErrorKind::LimitExceeded),
+            (
+                CodegenError::Unsupported("simd".into()),
+                CodegenErrorKind::Unsupported,
+            ),
+            (
+                CodegenError::NoLowering {
+                    inst: "v2 = iadd.i32 v0, v1".into(),
+                    opcode: Opcode::Iadd,
+                    ctrl_type: types::I32,
+                },
+                CodegenErrorKind::Unsupported,
+            ),
+            #[cfg(feature = "unwind")]
+            (
+                CodegenError::RegisterMappingError(
+                    crate::isa::unwind::systemv::RegisterMappingError::MissingBank,
+                ),
+                CodegenErrorKind::Internal,
+            ),
+            (
+                CodegenError::LegalizerLoop("v1 = iadd_imm.i32 v0, 1".into()),
+                CodegenErrorKind::Internal,
+            ),
+            (
+                CodegenError::BudgetExceeded("max_insts"),
+                CodegenErrorKind::LimitExceeded,
+            ),
+        ];
+        for (error, kind) in errors {
+            assert_eq!(error.kind(), kind, "{}", error);
+        }
+    }
+}
@@ -523,6 +523,7 @@ mod tests {
             r#"[shared]
 opt_level = "none"
 tls_model = "none"
+code_model = "small"
 libcall_call_conv = "isa_default"
 probestack_size_log2 = 12
 probestack_strategy = "outline"
@@ -544,6 +545,7 @@ enable_llvm_abi_extensions = false
 unwind_info = true
 preserve_frame_pointers = false
 machine_code_cfg_info = false
+align_loops = false
 enable_probestack = true
 probestack_func_adjusts_sp = false
 enable_jump_tables = true
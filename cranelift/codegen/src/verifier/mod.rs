@@ -66,8 +66,9 @@ use crate::ir;
 use crate::ir::entities::AnyEntity;
 use crate::ir::instructions::{BranchInfo, CallInfo, InstructionFormat, ResolvedConstraint};
 use crate::ir::{
-    types, ArgumentPurpose, Block, Constant, DynamicStackSlot, FuncRef, Function, GlobalValue,
-    Inst, JumpTable, Opcode, SigRef, StackSlot, Type, Value, ValueDef, ValueList,
+    types, ArgumentExtension, ArgumentPurpose, Block, Constant, DynamicStackSlot, FuncRef,
+    Function, GlobalValue, Inst, JumpTable, Opcode, SigRef, StackSlot, Type, Value, ValueDef,
+    ValueList,
 };
 use crate::isa::TargetIsa;
 use crate::iterators::IteratorExtras;
@@ -1770,6 +1771,52 @@ impl<'a> Verifier<'a> {
                 }
             });
 
+        // Only calls can pass variadic arguments: defining a variadic function would need
+        // `va_list` support.
+        if self.func.signature.variadic.is_some() {
+            errors.report((
+                AnyEntity::Function,
+                "variadic functions can be called but not defined",
+            ));
+        }
+
+        // Extension attributes tell the caller to extend a narrow integer to the full register
+        // width, which is meaningless for any other type.
+        let signatures = core::iter::once((AnyEntity::Function, &self.func.signature)).chain(
+            self.func
+                .dfg
+                .signatures
+                .iter()
+                .map(|(sig_ref, sig)| (AnyEntity::SigRef(sig_ref), sig)),
+        );
+        for (entity, sig) in signatures {
+            if matches!(sig.variadic, Some(fixed) if fixed > sig.params.len()) {
+                errors.report((
+                    entity,
+                    format!(
+                        "variadic signature has more fixed parameters than parameters: {}",
+                        sig
+                    ),
+                ));
+            }
+            let params = sig.params.iter().enumerate();
+            let params = params.map(|(i, param)| ("Parameter", i, param));
+            let returns = sig.returns.iter().enumerate();
+            let returns = returns.map(|(i, ret)| ("Return value", i, ret));
+            for (kind, i, param) in params.chain(returns) {
+                let ty = param.value_type;
+                if param.extension != ArgumentExtension::None && !ty.is_int() {
+                    errors.report((
+                        entity,
+                        format!(
+                            "{} at position {} has an extension attribute but isn't an integer: {}",
+                            kind, i, param
+                        ),
+                    ));
+                }
+            }
+        }
+
         if errors.has_error() {
             Err(())
         } else {
@@ -1818,7 +1865,8 @@ mod tests {
     use super::{Verifier, VerifierError, VerifierErrors};
     use crate::entity::EntityList;
     use crate::ir::instructions::{InstructionData, Opcode};
-    use crate::ir::{types, AbiParam, Function};
+    use crate::ir::{types, AbiParam, ArgumentExtension, Function, Signature};
+    use crate::isa::CallConv;
     use crate::settings;
 
     macro_rules! assert_err_with_msg {
@@ -1901,6 +1949,51 @@ mod tests {
         assert_err_with_msg!(errors, "Return value at position 0 has an invalid type");
     }
 
+    #[test]
+    fn test_function_invalid_extension() {
+        let mut func = Function::new();
+        func.signature.params.push(AbiParam::new(types::I8).sext());
+        func.signature.params.push(AbiParam {
+            extension: ArgumentExtension::Uext,
+            ..AbiParam::new(types::F32)
+        });
+
+        let mut errors = VerifierErrors::default();
+        let flags = &settings::Flags::new(settings::builder());
+        let verifier = Verifier::new(&func, flags.into());
+
+        let _ = verifier.typecheck_function_signature(&mut errors);
+        assert_eq!(errors.0.len(), 1);
+        assert_err_with_msg!(
+            errors,
+            "Parameter at position 1 has an extension attribute but isn't an integer: f32 uext"
+        );
+    }
+
+    #[test]
+    fn test_variadic_signatures() {
+        let mut func = Function::new();
+        func.signature.variadic = Some(0);
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(types::I64));
+        sig.variadic = Some(1);
+        func.import_signature(sig.clone());
+        sig.variadic = Some(2);
+        func.import_signature(sig);
+
+        let mut errors = VerifierErrors::default();
+        let flags = &settings::Flags::new(settings::builder());
+        let verifier = Verifier::new(&func, flags.into());
+
+        let _ = verifier.typecheck_function_signature(&mut errors);
+        assert_eq!(errors.0.len(), 2);
+        assert_err_with_msg!(errors, "variadic functions can be called but not defined");
+        assert_eq!(
+            errors.0[1].message,
+            "variadic signature has more fixed parameters than parameters: (i64, ...) system_v"
+        );
+    }
+
     #[test]
     fn test_printing_contextual_errors() {
         // Build function.
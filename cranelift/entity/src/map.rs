@@ -130,6 +130,16 @@ where
         self.elems.resize(n, self.default.clone());
     }
 
+    /// Reserves capacity for at least `additional` more entries beyond the current length.
+    pub fn reserve(&mut self, additional: usize) {
+        self.elems.reserve(additional)
+    }
+
+    /// Shrinks the capacity of the `SecondaryMap` as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.elems.shrink_to_fit()
+    }
+
     /// Slow path for `index_mut` which resizes the vector.
     #[cold]
     fn resize_for_index_mut(&mut self, i: usize) -> &mut V {
@@ -323,4 +333,21 @@ mod tests {
         assert_eq!(shared[r1], 5);
         assert_eq!(shared[r2], 3);
     }
+
+    #[test]
+    fn capacity() {
+        let mut m: SecondaryMap<E, u32> = SecondaryMap::with_capacity(100);
+        assert!(m.capacity() >= 100);
+
+        m[E(3)] = 1;
+        m.shrink_to_fit();
+        assert!(m.capacity() >= 4 && m.capacity() < 100);
+        assert_eq!(m[E(3)], 1);
+
+        m.reserve(10);
+        assert!(m.capacity() >= 14);
+
+        m.clear();
+        assert_eq!(m[E(3)], 0);
+    }
 }
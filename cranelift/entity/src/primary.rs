@@ -82,6 +82,11 @@ where
         self.elems.len()
     }
 
+    /// Returns the number of entities the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.elems.capacity()
+    }
+
     /// Iterate over all the keys in this map.
     pub fn keys(&self) -> Keys<K> {
         Keys::with_len(self.elems.len())
@@ -115,9 +115,9 @@ where
         self.elems[last_index / 8] &= !(1 << (last_index % 8));
 
         // Set the length to the next last stored entity or zero if we pop'ed
-        // the last entity.
-        self.len = self
-            .elems
+        // the last entity. No bits are set past `last_index`, so only the
+        // bytes up to it need to be searched.
+        self.len = self.elems[..=last_index / 8]
             .iter()
             .enumerate()
             .rev()
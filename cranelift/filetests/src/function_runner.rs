@@ -12,7 +12,7 @@ use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
 use cranelift_jit::{JITBuilder, JITModule};
 use cranelift_module::{FuncId, Linkage, Module, ModuleError};
 use cranelift_native::builder_with_options;
-use cranelift_reader::TestFile;
+use cranelift_reader::{Outcome, RunCommand, TestFile};
 use std::cmp::max;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
@@ -321,6 +321,80 @@ impl<'a> Trampoline<'a> {
 
         values.collect_returns(&self.func_signature)
     }
+
+    /// Call the target function the way `command` needs: if it expects a trap, the call is made in
+    /// a child process so that the caller survives the trap.
+    pub fn call_for_command(
+        &self,
+        command: &RunCommand,
+        arguments: &[DataValue],
+    ) -> Result<Outcome, String> {
+        match command {
+            RunCommand::Trap(_) => self.call_in_child_process(arguments),
+            RunCommand::Print(_) | RunCommand::Run(..) => Ok(Outcome::Return(self.call(arguments))),
+        }
+    }
+
+    /// Call the target function in a child process, reporting a trap if the child was killed by
+    /// the signal a trap instruction raises. Any other signal, such as the `SIGSEGV` of a stray
+    /// memory access, is an error. The values the function returns are not collected.
+    #[cfg(unix)]
+    fn call_in_child_process(&self, arguments: &[DataValue]) -> Result<Outcome, String> {
+        let mut values = UnboxedValues::make_arguments(arguments, self.func_signature);
+        let arguments_address = values.as_mut_ptr();
+
+        let function_ptr = self.module.get_finalized_function(self.func_id);
+        let trampoline_ptr = self.module.get_finalized_function(self.trampoline_id);
+
+        let callable_trampoline: fn(*const u8, *mut u128) -> () =
+            unsafe { mem::transmute(trampoline_ptr) };
+
+        match unsafe { libc::fork() } {
+            -1 => Err(format!("fork failed: {}", std::io::Error::last_os_error())),
+            0 => {
+                // This is the child. Only the calling thread exists here, so don't touch anything
+                // another thread may have held a lock on: the function and its arguments are set
+                // up before forking. Avoid dumping core when the function traps, and exit without
+                // running any of the parent's cleanup.
+                let no_core = libc::rlimit {
+                    rlim_cur: 0,
+                    rlim_max: 0,
+                };
+                unsafe { libc::setrlimit(libc::RLIMIT_CORE, &no_core) };
+                callable_trampoline(function_ptr, arguments_address);
+                unsafe { libc::_exit(0) }
+            }
+            child => {
+                let mut status = 0;
+                if unsafe { libc::waitpid(child, &mut status, 0) } == -1 {
+                    return Err(format!(
+                        "waitpid failed: {}",
+                        std::io::Error::last_os_error()
+                    ));
+                }
+                if !libc::WIFSIGNALED(status) {
+                    return Ok(Outcome::Return(vec![]));
+                }
+                // The trap instructions of every backend are undefined instructions, which raise
+                // `SIGILL`. `SIGTRAP` comes from breakpoint instructions such as `debugtrap`, and
+                // x86 division instructions raise `SIGFPE` themselves when dividing by zero.
+                match libc::WTERMSIG(status) {
+                    libc::SIGILL => Ok(Outcome::Trap("SIGILL".to_string())),
+                    libc::SIGTRAP => Ok(Outcome::Trap("SIGTRAP".to_string())),
+                    libc::SIGFPE => Ok(Outcome::Trap("SIGFPE".to_string())),
+                    signal => Err(format!(
+                        "the function was killed by signal {}, which isn't raised by traps",
+                        signal
+                    )),
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn call_in_child_process(&self, _arguments: &[DataValue]) -> Result<Outcome, String> {
+        Err("expecting a trap is only supported on Unix hosts".to_string())
+    }
 }
 
 /// Compilation Error when compiling a function.
@@ -38,6 +38,7 @@ mod runtest_environment;
 mod subtest;
 
 mod test_alias_analysis;
+mod test_binemit;
 mod test_cat;
 mod test_compile;
 mod test_dce;
@@ -113,6 +114,7 @@ pub fn run_passes(
 fn new_subtest(parsed: &TestCommand) -> anyhow::Result<Box<dyn subtest::SubTest>> {
     match parsed.command {
         "alias-analysis" => test_alias_analysis::subtest(parsed),
+        "binemit" => test_binemit::subtest(parsed),
         "cat" => test_cat::subtest(parsed),
         "compile" => test_compile::subtest(parsed),
         "dce" => test_dce::subtest(parsed),
@@ -3,7 +3,7 @@
 use crate::new_subtest;
 use crate::subtest::SubTest;
 use anyhow::{bail, Context as _, Result};
-use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::isa::{self, TargetIsa};
 use cranelift_codegen::print_errors::pretty_verifier_error;
 use cranelift_codegen::settings::{Flags, FlagsOrIsa};
 use cranelift_codegen::timing;
@@ -75,8 +75,15 @@ pub fn run(
     // the front.
     tests.sort_by_key(|st| (st.is_mutating(), st.needs_verifier()));
 
+    // A file that doesn't name any target runs its ISA-dependent tests on every backend that
+    // is built in, so that they can be checked against each other.
+    let default_isas = match testfile.isa_spec {
+        IsaSpec::None(ref f) if tests.iter().any(|st| st.needs_isa()) => default_isas(f)?,
+        _ => Vec::new(),
+    };
+
     // Expand the tests into (test, flags, isa) tuples.
-    let tuples = test_tuples(&tests, &testfile.isa_spec, flags)?;
+    let tuples = test_tuples(&tests, &testfile.isa_spec, flags, &default_isas)?;
 
     // Bail if the test has no runnable commands
     if tuples.is_empty() {
@@ -108,19 +115,35 @@ fn verify_testfile(testfile: &TestFile, fisa: FlagsOrIsa) -> anyhow::Result<()>
     Ok(())
 }
 
+// Build an ISA with the given flags for every backend enabled in this build.
+fn default_isas(flags: &Flags) -> anyhow::Result<Vec<Box<dyn TargetIsa>>> {
+    let mut isas = Vec::new();
+    for name in ["x86_64", "aarch64", "s390x", "riscv64"] {
+        if let Ok(builder) = isa::lookup_by_name(name) {
+            isas.push(builder.finish(flags.clone())?);
+        }
+    }
+    Ok(isas)
+}
+
 // Given a slice of tests, generate a vector of (test, flags, isa) tuples.
 fn test_tuples<'a>(
     tests: &'a [Box<dyn SubTest>],
     isa_spec: &'a IsaSpec,
     no_isa_flags: &'a Flags,
+    default_isas: &'a [Box<dyn TargetIsa>],
 ) -> anyhow::Result<Vec<(&'a dyn SubTest, &'a Flags, Option<&'a dyn TargetIsa>)>> {
     let mut out = Vec::new();
     for test in tests {
         if test.needs_isa() {
             match *isa_spec {
                 IsaSpec::None(_) => {
-                    // TODO: Generate a list of default ISAs.
-                    anyhow::bail!("test {} requires an ISA", test.name());
+                    if default_isas.is_empty() {
+                        anyhow::bail!("test {} requires an ISA", test.name());
+                    }
+                    for isa in default_isas {
+                        out.push((&**test, isa.flags(), Some(&**isa)));
+                    }
                 }
                 IsaSpec::Some(ref isas) => {
                     for isa in isas {
@@ -0,0 +1,134 @@
+//! Test command for testing the binary machine code emitted for functions.
+//!
+//! The `binemit` test command compiles each function and renders the emitted bytes as hex, grouped
+//! by the IR instruction they were generated from and annotated with relocations and traps. The
+//! expectation is given as comments after the function, as with `test compile precise-output`,
+//! and can likewise be updated automatically with `CRANELIFT_TEST_BLESS=1`.
+
+use crate::subtest::{Context, SubTest};
+use crate::test_compile::check_precise_output;
+use anyhow::Result;
+use cranelift_codegen::ir::{self, SourceLoc};
+use cranelift_codegen::CompiledCode;
+use cranelift_reader::TestCommand;
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Number of bytes printed on each line of hex output.
+const BYTES_PER_LINE: usize = 8;
+
+struct TestBinEmit;
+
+pub fn subtest(parsed: &TestCommand) -> Result<Box<dyn SubTest>> {
+    assert_eq!(parsed.command, "binemit");
+    if !parsed.options.is_empty() {
+        anyhow::bail!("No options allowed on {}", parsed);
+    }
+    Ok(Box::new(TestBinEmit))
+}
+
+impl SubTest for TestBinEmit {
+    fn name(&self) -> &'static str {
+        "binemit"
+    }
+
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    fn needs_isa(&self) -> bool {
+        true
+    }
+
+    fn run(&self, func: Cow<ir::Function>, context: &Context) -> Result<()> {
+        let isa = context.isa.expect("binemit needs an ISA");
+        let mut func = func.into_owned();
+
+        // Tag every instruction with its position as source location, so that the emitted code
+        // can be mapped back to the instruction it came from.
+        let layout_insts = func
+            .layout
+            .blocks()
+            .flat_map(|block| func.layout.block_insts(block))
+            .collect::<Vec<_>>();
+        let mut insts = vec![];
+        for (index, inst) in layout_insts.into_iter().enumerate() {
+            insts.push(func.dfg.display_inst(inst).to_string());
+            func.set_srcloc(inst, SourceLoc::new(index as u32));
+        }
+
+        let mut comp_ctx = cranelift_codegen::Context::for_function(func);
+        comp_ctx
+            .compile(isa)
+            .map_err(|e| crate::pretty_anyhow_error(e.func, e.inner))?;
+        let compiled_code = comp_ctx.compiled_code().unwrap();
+
+        let text = render(compiled_code, &insts, &comp_ctx.func.params);
+        check_precise_output(&text, context)
+    }
+}
+
+/// Render the emitted code as hex, in chunks labelled with the IR instruction they belong to.
+fn render(
+    compiled_code: &CompiledCode,
+    insts: &[String],
+    params: &ir::function::FunctionParameters,
+) -> String {
+    let buffer = &compiled_code.buffer;
+    let data = buffer.data();
+
+    // Split the code into chunks, filling the gaps between source location ranges (and the
+    // constants and islands at the end) with unlabelled chunks.
+    let mut chunks = vec![];
+    let mut offset = 0;
+    for srcloc in buffer.get_srclocs_sorted() {
+        if srcloc.start > offset {
+            chunks.push((offset, srcloc.start, None));
+        }
+        let inst = if srcloc.loc.is_default() {
+            None
+        } else {
+            insts.get(srcloc.loc.bits() as usize)
+        };
+        chunks.push((srcloc.start, srcloc.end, inst));
+        offset = srcloc.end;
+    }
+    if (offset as usize) < data.len() {
+        chunks.push((offset, data.len() as u32, None));
+    }
+
+    let mut text = String::new();
+    for (start, end, inst) in chunks {
+        match inst {
+            Some(inst) => writeln!(text, "{}", inst).unwrap(),
+            None => writeln!(text, "<none>").unwrap(),
+        }
+        let bytes = &data[start as usize..end as usize];
+        for (i, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+            write!(text, "  {:04x}:", start as usize + i * BYTES_PER_LINE).unwrap();
+            for byte in line {
+                write!(text, " {:02x}", byte).unwrap();
+            }
+            writeln!(text).unwrap();
+        }
+        for reloc in buffer.relocs() {
+            if (start..end).contains(&reloc.offset) {
+                writeln!(
+                    text,
+                    "  {:04x}: reloc {} {}{:+}",
+                    reloc.offset,
+                    reloc.kind,
+                    reloc.name.display(Some(params)),
+                    reloc.addend
+                )
+                .unwrap();
+            }
+        }
+        for trap in buffer.traps() {
+            if (start..end).contains(&trap.offset) {
+                writeln!(text, "  {:04x}: trap {}", trap.offset, trap.code).unwrap();
+            }
+        }
+    }
+    text
+}
@@ -70,7 +70,7 @@ impl SubTest for TestCompile {
     }
 }
 
-fn check_precise_output(text: &str, context: &Context) -> Result<()> {
+pub(crate) fn check_precise_output(text: &str, context: &Context) -> Result<()> {
     let actual = text.lines().collect::<Vec<_>>();
 
     // Use the comments after the function to build the test expectation.
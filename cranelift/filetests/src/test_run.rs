@@ -13,7 +13,7 @@ use cranelift_codegen::isa::TargetIsa;
 use cranelift_codegen::settings::{Configurable, Flags};
 use cranelift_codegen::{ir, settings};
 use cranelift_reader::TestCommand;
-use cranelift_reader::{parse_run_command, TestFile};
+use cranelift_reader::{parse_run_command, RunCommand, TestFile};
 use log::{info, trace};
 use std::borrow::Cow;
 use target_lexicon::Architecture;
@@ -137,6 +137,16 @@ fn compile_testfile(
     Ok(tfc.compile()?)
 }
 
+fn compile_only(testfile: &TestFile, isa: &dyn TargetIsa) -> anyhow::Result<()> {
+    for (func, _) in &testfile.functions {
+        let mut ctx = cranelift_codegen::Context::for_function(func.clone());
+        ctx.compile(isa)
+            .map_err(|e| crate::pretty_anyhow_error(e.func, e.inner))
+            .with_context(|| format!("failed to compile {} for {}", func.name, isa.name()))?;
+    }
+    Ok(())
+}
+
 fn run_test(
     testfile: &CompiledTestFile,
     func: &ir::Function,
@@ -147,6 +157,13 @@ fn run_test(
     for comment in context.details.comments.iter() {
         if let Some(command) = parse_run_command(comment.text, &func.signature)? {
             trace!("Parsed run command: {}", command);
+            if cfg!(not(unix)) && matches!(command, RunCommand::Trap(_)) {
+                info!(
+                    "skipped {}: traps can only be caught on Unix hosts",
+                    command
+                );
+                continue;
+            }
 
             command
                 .run(|_, run_args| {
@@ -161,7 +178,7 @@ fn run_test(
                     args.extend_from_slice(run_args);
 
                     let trampoline = testfile.get_trampoline(func).unwrap();
-                    Ok(trampoline.call(&args))
+                    trampoline.call_for_command(&command, &args)
                 })
                 .map_err(|s| anyhow::anyhow!("{}", s))?;
         }
@@ -206,7 +223,9 @@ impl SubTest for TestRun {
         let host_isa = build_host_isa(true, flags.clone(), vec![]);
         if let Err(e) = is_isa_compatible(file_path, host_isa.as_ref(), isa.unwrap()) {
             log::info!("{}", e);
-            return Ok(());
+            // We can't execute the code, but still check that every function compiles for
+            // the requested target.
+            return compile_only(testfile, isa.unwrap());
         }
 
         let compiled_testfile = compile_testfile(&testfile, flags, isa.unwrap())?;
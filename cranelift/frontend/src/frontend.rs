@@ -1,6 +1,7 @@
 //! A frontend for building Cranelift IR from other languages.
 use crate::ssa::{SSABuilder, SideEffects};
 use crate::variable::Variable;
+use core::convert::TryInto; // FIXME: Remove in edition2021
 use core::fmt::{self, Debug};
 use cranelift_codegen::cursor::{Cursor, FuncCursor};
 use cranelift_codegen::entity::{EntityRef, EntitySet, SecondaryMap};
@@ -15,7 +16,6 @@ use cranelift_codegen::ir::{
 };
 use cranelift_codegen::isa::TargetFrontendConfig;
 use cranelift_codegen::packed_option::PackedOption;
-use std::convert::TryInto; // FIXME: Remove in edition2021
 
 /// Structure used for translating a series of functions into Cranelift IR.
 ///
@@ -184,6 +184,7 @@ impl fmt::Display for UseVariableError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for UseVariableError {}
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -192,6 +193,7 @@ pub enum DeclareVariableError {
     DeclaredMultipleTimes(Variable),
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for DeclareVariableError {}
 
 impl fmt::Display for DeclareVariableError {
@@ -1013,8 +1015,8 @@ impl<'a> FunctionBuilder<'a> {
         left: Value,
         right: Value,
         size: u64,
-        left_align: std::num::NonZeroU8,
-        right_align: std::num::NonZeroU8,
+        left_align: core::num::NonZeroU8,
+        right_align: core::num::NonZeroU8,
         flags: MemFlags,
     ) -> Value {
         use IntCC::*;
@@ -35,6 +35,11 @@ use smallvec::SmallVec;
 /// A basic block is said _filled_ if all the instruction that it contains have been translated,
 /// and it is said _sealed_ if all of its predecessors have been declared. Only filled predecessors
 /// can be declared.
+///
+/// The construction doesn't rely on the CFG being reducible: a block with several entry points
+/// into a cycle gets the same block parameters as a regular loop header, so there is no need to
+/// detect or split irreducible control flow beforehand. The resulting SSA form may contain
+/// redundant block parameters in that case, but it is always correct.
 pub struct SSABuilder {
     // TODO: Consider a sparse representation rather than SecondaryMap-of-SecondaryMap.
     /// Records for every variable and for every relevant block, the last definition of
@@ -755,7 +760,7 @@ mod tests {
     use cranelift_codegen::entity::EntityRef;
     use cranelift_codegen::ir::instructions::BranchInfo;
     use cranelift_codegen::ir::types::*;
-    use cranelift_codegen::ir::{Function, Inst, InstBuilder, JumpTableData, Opcode};
+    use cranelift_codegen::ir::{AbiParam, Function, Inst, InstBuilder, JumpTableData, Opcode};
     use cranelift_codegen::settings;
     use cranelift_codegen::verify_function;
 
@@ -1060,6 +1065,121 @@ mod tests {
         assert_eq!(func.dfg.resolve_aliases(x3), x1);
     }
 
+    #[test]
+    fn irreducible_control_flow() {
+        let mut func = Function::new();
+        func.signature.returns.push(AbiParam::new(I32));
+        let mut ssa = SSABuilder::new();
+        let block0 = func.dfg.make_block();
+        let block1 = func.dfg.make_block();
+        let block2 = func.dfg.make_block();
+        let block3 = func.dfg.make_block();
+        {
+            let mut cur = FuncCursor::new(&mut func);
+            cur.insert_block(block0);
+            cur.insert_block(block1);
+            cur.insert_block(block2);
+            cur.insert_block(block3);
+        }
+        // Here is the pseudo-program we want to translate, where the cycle between block1 and
+        // block2 can be entered through either block:
+        // block0:
+        //    x = 1;
+        //    c = 0;
+        //    brnz c, block2;
+        //    jump block1;
+        // block1:
+        //    x = x + 1;
+        //    jump block2;
+        // block2:
+        //    brnz c, block1;
+        //    jump block3;
+        // block3:
+        //    return x;
+
+        // block0
+        ssa.declare_block(block0);
+        ssa.seal_block(block0, &mut func);
+        let x_var = Variable::new(0);
+        let (x1, c) = {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block0);
+            (cur.ins().iconst(I32, 1), cur.ins().iconst(I32, 0))
+        };
+        ssa.def_var(x_var, x1, block0);
+        let brnz_block0_block2 = {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block0);
+            cur.ins().brnz(c, block2, &[])
+        };
+        let jump_block0_block1 = {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block0);
+            cur.ins().jump(block1, &[])
+        };
+
+        // block1, whose predecessor block2 hasn't been filled yet.
+        ssa.declare_block(block1);
+        ssa.declare_block_predecessor(block1, block0, jump_block0_block1);
+        let x2 = ssa.use_var(&mut func, x_var, I32, block1).0;
+        let x3 = {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block1);
+            cur.ins().iadd_imm(x2, 1)
+        };
+        ssa.def_var(x_var, x3, block1);
+        let jump_block1_block2 = {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block1);
+            cur.ins().jump(block2, &[])
+        };
+
+        // block2
+        ssa.declare_block(block2);
+        ssa.declare_block_predecessor(block2, block0, brnz_block0_block2);
+        ssa.declare_block_predecessor(block2, block1, jump_block1_block2);
+        ssa.seal_block(block2, &mut func);
+        let x4 = ssa.use_var(&mut func, x_var, I32, block2).0;
+        let brnz_block2_block1 = {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block2);
+            cur.ins().brnz(c, block1, &[])
+        };
+        let jump_block2_block3 = {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block2);
+            cur.ins().jump(block3, &[])
+        };
+
+        // block3
+        ssa.declare_block(block3);
+        ssa.declare_block_predecessor(block3, block2, jump_block2_block3);
+        ssa.seal_block(block3, &mut func);
+        let x5 = ssa.use_var(&mut func, x_var, I32, block3).0;
+        assert_eq!(x5, x4);
+        {
+            let mut cur = FuncCursor::new(&mut func).at_bottom(block3);
+            cur.ins().return_(&[x5])
+        };
+
+        // block1 after all predecessors have been visited.
+        ssa.declare_block_predecessor(block1, block2, brnz_block2_block1);
+        ssa.seal_block(block1, &mut func);
+
+        // Both entry points of the cycle merge the value coming from block0 with the one coming
+        // from the other block of the cycle.
+        assert_eq!(func.dfg.block_params(block1), &[x2]);
+        assert_eq!(func.dfg.block_params(block2), &[x4]);
+        assert_eq!(func.dfg.inst_variable_args(jump_block0_block1), &[x1]);
+        assert_eq!(func.dfg.inst_variable_args(brnz_block2_block1), &[x4]);
+        assert_eq!(func.dfg.inst_variable_args(brnz_block0_block2), &[x1]);
+        assert_eq!(func.dfg.inst_variable_args(jump_block1_block2), &[x3]);
+
+        let flags = settings::Flags::new(settings::builder());
+        match verify_function(&func, &flags) {
+            Ok(()) => {}
+            Err(_errors) => {
+                #[cfg(feature = "std")]
+                panic!("{}", _errors);
+                #[cfg(not(feature = "std"))]
+                panic!("function failed to verify");
+            }
+        }
+    }
+
     #[test]
     fn br_table_with_args() {
         // This tests the on-demand splitting of critical edges for br_table with jump arguments
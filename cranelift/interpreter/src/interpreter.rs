@@ -7,7 +7,7 @@ use crate::environment::{FuncIndex, FunctionStore};
 use crate::frame::Frame;
 use crate::instruction::DfgInstructionContext;
 use crate::state::{MemoryError, State};
-use crate::step::{step, ControlFlow, StepError};
+use crate::step::{step, ControlFlow, CraneliftTrap, StepError};
 use crate::value::{Value, ValueError};
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
@@ -151,6 +151,32 @@ impl<'a> Interpreter<'a> {
     }
 }
 
+/// Evaluate a constant expression, such as a Wasm global or data segment initializer, that has
+/// been translated into a function taking no arguments, and return the values it produces.
+///
+/// The function can't call other functions or access heaps, but may use stack slots. `fuel`
+/// bounds the number of instructions executed (see [Interpreter::with_fuel]).
+pub fn evaluate_constant(
+    func: &Function,
+    fuel: Option<u64>,
+) -> Result<Vec<DataValue>, InterpreterError> {
+    if !func.signature.params.is_empty() {
+        return Err(InterpreterError::NotAConstant(func.name.to_string()));
+    }
+    let name = func.name.to_string();
+    let mut env = FunctionStore::default();
+    env.add(name.clone(), func);
+    let state = InterpreterState::default().with_function_store(env);
+    match Interpreter::new(state)
+        .with_fuel(fuel)
+        .call_by_name(&name, &[])?
+    {
+        ControlFlow::Return(values) => Ok(values.into_vec()),
+        ControlFlow::Trap(trap) => Err(InterpreterError::Trapped(trap)),
+        _ => Err(InterpreterError::Unreachable),
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 /// The result of consuming fuel. Signals if the caller should stop or continue.
 pub enum FuelResult {
@@ -175,6 +201,10 @@ pub enum InterpreterError {
     ValueError(#[from] ValueError),
     #[error("fuel exhausted")]
     FuelExhausted,
+    #[error("trapped: {0}")]
+    Trapped(CraneliftTrap),
+    #[error("{0} takes arguments, so it isn't a constant expression")]
+    NotAConstant(String),
 }
 
 pub type HeapBacking = Vec<u8>;
@@ -627,7 +657,6 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::step::CraneliftTrap;
     use cranelift_codegen::ir::immediates::Ieee32;
     use cranelift_codegen::ir::types::I64;
     use cranelift_codegen::ir::TrapCode;
@@ -660,6 +689,56 @@ mod tests {
         assert_eq!(result, vec![DataValue::B(true)])
     }
 
+    #[test]
+    fn constant_evaluation() {
+        let code = "function %init() -> i64, f32 {
+            ss0 = explicit_slot 8
+        block0:
+            v0 = iconst.i64 40
+            stack_store v0, ss0
+            v1 = stack_load.i64 ss0
+            v2 = iadd_imm v1, 2
+            v3 = f32const 0x1.5p0
+            return v2, v3
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let result = evaluate_constant(&func, Some(100)).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                DataValue::I64(42),
+                DataValue::F32(Ieee32::with_float(1.3125))
+            ]
+        );
+
+        let code = "function %init() -> i32 {
+        block0:
+            v0 = iconst.i32 1
+            v1 = iconst.i32 0
+            v2 = udiv v0, v1
+            return v2
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let result = evaluate_constant(&func, None);
+        assert!(matches!(
+            result,
+            Err(InterpreterError::Trapped(CraneliftTrap::User(
+                TrapCode::IntegerDivisionByZero
+            )))
+        ));
+
+        let code = "function %init(i32) -> i32 {
+        block0(v0: i32):
+            return v0
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let result = evaluate_constant(&func, None);
+        assert!(matches!(result, Err(InterpreterError::NotAConstant(_))));
+    }
+
     // We don't have a way to check for traps with the current filetest infrastructure
     #[test]
     fn udiv_by_zero_traps() {
@@ -507,12 +507,18 @@ where
         Opcode::SymbolValue => unimplemented!("SymbolValue"),
         Opcode::TlsValue => unimplemented!("TlsValue"),
         Opcode::HeapAddr => {
-            if let InstructionData::HeapAddr { heap, .. } = inst {
+            if let InstructionData::HeapAddr { heap, imm, .. } = inst {
                 let load_ty = inst_context.controlling_type().unwrap();
-                let offset = calculate_addr(ctrl_ty, imm(), args()?)? as u64;
+                let index = arg(0)?
+                    .convert(ValueConversionKind::ZeroExtend(ctrl_ty))?
+                    .into_int()? as u64;
+                let size = u64::from(u32::from(imm));
                 assign_or_memtrap({
                     AddressSize::try_from(load_ty).and_then(|addr_size| {
-                        let addr = state.heap_address(addr_size, heap, offset)?;
+                        // The whole access has to be in bounds, but the result is the address of
+                        // its first byte.
+                        state.heap_address(addr_size, heap, index + size)?;
+                        let addr = state.heap_address(addr_size, heap, index)?;
                         let dv = DataValue::try_from(addr)?;
                         Ok(dv.into())
                     })
@@ -753,16 +759,19 @@ where
         Opcode::IaddIfcin => unimplemented!("IaddIfcin"),
         Opcode::IaddCout => {
             let sum = Value::add(arg(0)?, arg(1)?)?;
-            let carry = Value::lt(&sum, &arg(0)?)? && Value::lt(&sum, &arg(1)?)?;
+            let carry = icmp(ctrl_ty, IntCC::UnsignedLessThan, &sum, &arg(0)?)?.into_bool()?;
             assign_multiple(&[sum, Value::bool(carry, types::B1)?])
         }
         Opcode::IaddIfcout => unimplemented!("IaddIfcout"),
         Opcode::IaddCarry => {
             let mut sum = Value::add(arg(0)?, arg(1)?)?;
+            let mut carry = icmp(ctrl_ty, IntCC::UnsignedLessThan, &sum, &arg(0)?)?.into_bool()?;
             if Value::into_bool(arg(2)?)? {
-                sum = Value::add(sum, Value::int(1, ctrl_ty)?)?
+                let one = Value::int(1, ctrl_ty)?;
+                let sum_in = Value::add(sum.clone(), one)?;
+                carry |= icmp(ctrl_ty, IntCC::UnsignedLessThan, &sum_in, &sum)?.into_bool()?;
+                sum = sum_in;
             }
-            let carry = Value::lt(&sum, &arg(0)?)? && Value::lt(&sum, &arg(1)?)?;
             assign_multiple(&[sum, Value::bool(carry, types::B1)?])
         }
         Opcode::IaddIfcarry => unimplemented!("IaddIfcarry"),
@@ -774,18 +783,19 @@ where
         Opcode::IsubIfbin => unimplemented!("IsubIfbin"),
         Opcode::IsubBout => {
             let sum = Value::sub(arg(0)?, arg(1)?)?;
-            let borrow = Value::lt(&arg(0)?, &arg(1)?)?;
+            let borrow = icmp(ctrl_ty, IntCC::UnsignedLessThan, &arg(0)?, &arg(1)?)?.into_bool()?;
             assign_multiple(&[sum, Value::bool(borrow, types::B1)?])
         }
         Opcode::IsubIfbout => unimplemented!("IsubIfbout"),
         Opcode::IsubBorrow => {
-            let rhs = if Value::into_bool(arg(2)?)? {
-                Value::add(arg(1)?, Value::int(1, ctrl_ty)?)?
-            } else {
-                arg(1)?
-            };
-            let borrow = Value::lt(&arg(0)?, &rhs)?;
-            let sum = Value::sub(arg(0)?, rhs)?;
+            let mut sum = Value::sub(arg(0)?, arg(1)?)?;
+            let mut borrow =
+                icmp(ctrl_ty, IntCC::UnsignedLessThan, &arg(0)?, &arg(1)?)?.into_bool()?;
+            if Value::into_bool(arg(2)?)? {
+                let one = Value::int(1, ctrl_ty)?;
+                borrow |= icmp(ctrl_ty, IntCC::UnsignedLessThan, &sum, &one)?.into_bool()?;
+                sum = Value::sub(sum, one)?;
+            }
             assign_multiple(&[sum, Value::bool(borrow, types::B1)?])
         }
         Opcode::IsubIfborrow => unimplemented!("IsubIfborrow"),
@@ -810,6 +820,7 @@ where
         Opcode::UshrImm => binary_unsigned(Value::ushr, arg(0)?, imm_as_ctrl_ty()?)?,
         Opcode::SshrImm => binary(Value::ishr, arg(0)?, imm_as_ctrl_ty()?)?,
         Opcode::Bitrev => assign(Value::reverse_bits(arg(0)?)?),
+        Opcode::Bswap => assign(Value::swap_bytes(arg(0)?)?),
         Opcode::Clz => assign(arg(0)?.leading_zeros()?),
         Opcode::Cls => {
             let count = if Value::lt(&arg(0)?, &Value::int(0, ctrl_ty)?)? {
@@ -1573,8 +1584,12 @@ where
             if unsigned {
                 lhs = lhs.convert(ValueConversionKind::ToUnsigned)?;
                 rhs = rhs.convert(ValueConversionKind::ToUnsigned)?;
+                // Scalar results are returned as they are, so convert them back to the signed
+                // representation used for the controlling type.
+                op(lhs, rhs)?.convert(ValueConversionKind::ToSigned)
+            } else {
+                op(lhs, rhs)
             }
-            Ok(op(lhs, rhs)?)
         })
         .collect::<ValueResult<SimdVec<V>>>()?;
 
@@ -0,0 +1,226 @@
+//! A small Brainfuck JIT, compiling and running programs end to end through the public Cranelift
+//! API: `cranelift-native` for host ISA detection, `cranelift-frontend` to build the IR and
+//! `cranelift-jit` to link it against host functions and run it.
+//!
+//! This example is also run by `cargo test`, so it doubles as an integration test.
+
+use cranelift::prelude::*;
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Linkage, Module};
+use std::mem;
+
+/// Number of cells on the tape. Must be a power of two: the data pointer wraps around at the ends.
+const TAPE_SIZE: usize = 1 << 15;
+
+/// The input and output of a running program, reachable from the generated code through the
+/// `bf_getchar` and `bf_putchar` host functions.
+struct Io<'a> {
+    input: &'a [u8],
+    output: Vec<u8>,
+}
+
+extern "C" fn bf_getchar(io: *mut Io) -> u8 {
+    let io = unsafe { &mut *io };
+    match io.input.split_first() {
+        Some((&byte, rest)) => {
+            io.input = rest;
+            byte
+        }
+        // Leave the cell zeroed at the end of the input.
+        None => 0,
+    }
+}
+
+extern "C" fn bf_putchar(io: *mut Io, byte: u8) {
+    let io = unsafe { &mut *io };
+    io.output.push(byte);
+}
+
+/// A Brainfuck program compiled to native code.
+struct Program {
+    // Owns the code that `code` points into.
+    _module: JITModule,
+    code: extern "C" fn(*mut u8, *mut Io),
+}
+
+impl Program {
+    /// Compile `source`, ignoring any characters which aren't Brainfuck commands.
+    fn compile(source: &str) -> Result<Self, String> {
+        let mut flag_builder = settings::builder();
+        flag_builder.set("use_colocated_libcalls", "false").unwrap();
+        // FIXME set back to true once the x64 backend supports it.
+        flag_builder.set("is_pic", "false").unwrap();
+        flag_builder.set("opt_level", "speed").unwrap();
+        let isa_builder = cranelift_native::builder()
+            .map_err(|msg| format!("host machine is not supported: {}", msg))?;
+        let isa = isa_builder
+            .finish(settings::Flags::new(flag_builder))
+            .map_err(|err| err.to_string())?;
+
+        let mut jit_builder = JITBuilder::with_isa(isa, default_libcall_names());
+        jit_builder.symbol("bf_getchar", bf_getchar as *const u8);
+        jit_builder.symbol("bf_putchar", bf_putchar as *const u8);
+        let mut module = JITModule::new(jit_builder);
+        let ptr = module.target_config().pointer_type();
+
+        let mut getchar_sig = module.make_signature();
+        getchar_sig.params.push(AbiParam::new(ptr));
+        getchar_sig.returns.push(AbiParam::new(types::I8));
+        let getchar = module
+            .declare_function("bf_getchar", Linkage::Import, &getchar_sig)
+            .map_err(|err| err.to_string())?;
+
+        let mut putchar_sig = module.make_signature();
+        putchar_sig.params.push(AbiParam::new(ptr));
+        putchar_sig.params.push(AbiParam::new(types::I8));
+        let putchar = module
+            .declare_function("bf_putchar", Linkage::Import, &putchar_sig)
+            .map_err(|err| err.to_string())?;
+
+        let mut sig = module.make_signature();
+        sig.params.push(AbiParam::new(ptr));
+        sig.params.push(AbiParam::new(ptr));
+        let main = module
+            .declare_function("main", Linkage::Local, &sig)
+            .map_err(|err| err.to_string())?;
+
+        let mut ctx = module.make_context();
+        ctx.func.signature = sig;
+        let mut func_ctx = FunctionBuilderContext::new();
+        {
+            let mut bcx = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+            let getchar = module.declare_func_in_func(getchar, bcx.func);
+            let putchar = module.declare_func_in_func(putchar, bcx.func);
+
+            let entry = bcx.create_block();
+            bcx.append_block_params_for_function_params(entry);
+            bcx.switch_to_block(entry);
+            bcx.seal_block(entry);
+            let tape = bcx.block_params(entry)[0];
+            let io = bcx.block_params(entry)[1];
+
+            // The offset of the current cell, always kept in `0..TAPE_SIZE`.
+            let offset = Variable::new(0);
+            bcx.declare_var(offset, ptr);
+            let zero = bcx.ins().iconst(ptr, 0);
+            bcx.def_var(offset, zero);
+
+            let cell_addr = |bcx: &mut FunctionBuilder| {
+                let offset = bcx.use_var(offset);
+                bcx.ins().iadd(tape, offset)
+            };
+
+            // The header and exit blocks of the loops enclosing the current command.
+            let mut loops = vec![];
+            for (pos, command) in source.char_indices() {
+                match command {
+                    '>' | '<' => {
+                        let step = if command == '>' { 1 } else { -1 };
+                        let value = bcx.use_var(offset);
+                        let value = bcx.ins().iadd_imm(value, step);
+                        let value = bcx.ins().band_imm(value, TAPE_SIZE as i64 - 1);
+                        bcx.def_var(offset, value);
+                    }
+                    '+' | '-' => {
+                        let step = if command == '+' { 1 } else { -1 };
+                        let addr = cell_addr(&mut bcx);
+                        let value = bcx.ins().load(types::I8, MemFlags::trusted(), addr, 0);
+                        let value = bcx.ins().iadd_imm(value, step);
+                        bcx.ins().store(MemFlags::trusted(), value, addr, 0);
+                    }
+                    ',' => {
+                        let call = bcx.ins().call(getchar, &[io]);
+                        let value = bcx.inst_results(call)[0];
+                        let addr = cell_addr(&mut bcx);
+                        bcx.ins().store(MemFlags::trusted(), value, addr, 0);
+                    }
+                    '.' => {
+                        let addr = cell_addr(&mut bcx);
+                        let value = bcx.ins().load(types::I8, MemFlags::trusted(), addr, 0);
+                        bcx.ins().call(putchar, &[io, value]);
+                    }
+                    '[' => {
+                        let header = bcx.create_block();
+                        let body = bcx.create_block();
+                        let exit = bcx.create_block();
+                        bcx.ins().jump(header, &[]);
+
+                        bcx.switch_to_block(header);
+                        let addr = cell_addr(&mut bcx);
+                        let value = bcx.ins().load(types::I8, MemFlags::trusted(), addr, 0);
+                        bcx.ins().brz(value, exit, &[]);
+                        bcx.ins().jump(body, &[]);
+
+                        bcx.switch_to_block(body);
+                        bcx.seal_block(body);
+                        loops.push((header, exit));
+                    }
+                    ']' => {
+                        let (header, exit) = loops
+                            .pop()
+                            .ok_or_else(|| format!("unmatched `]` at offset {}", pos))?;
+                        bcx.ins().jump(header, &[]);
+                        bcx.seal_block(header);
+
+                        bcx.switch_to_block(exit);
+                        bcx.seal_block(exit);
+                    }
+                    _ => {}
+                }
+            }
+            if !loops.is_empty() {
+                return Err(format!("{} unmatched `[`", loops.len()));
+            }
+            bcx.ins().return_(&[]);
+            bcx.finalize();
+        }
+
+        module
+            .define_function(main, &mut ctx)
+            .map_err(|err| err.to_string())?;
+        module.clear_context(&mut ctx);
+        module.finalize_definitions();
+
+        let code = module.get_finalized_function(main);
+        let code = unsafe { mem::transmute::<*const u8, extern "C" fn(*mut u8, *mut Io)>(code) };
+        Ok(Self {
+            _module: module,
+            code,
+        })
+    }
+
+    /// Run the program on a fresh tape, returning everything it wrote.
+    fn run(&self, input: &[u8]) -> Vec<u8> {
+        let mut tape = vec![0u8; TAPE_SIZE];
+        let mut io = Io {
+            input,
+            output: vec![],
+        };
+        (self.code)(tape.as_mut_ptr(), &mut io);
+        io.output
+    }
+}
+
+fn main() {
+    let hello = Program::compile(
+        "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.\
+         --------.>>+.>++.",
+    )
+    .unwrap();
+    let output = hello.run(b"");
+    print!("{}", String::from_utf8_lossy(&output));
+    assert_eq!(output, b"Hello World!\n");
+
+    // Reverse the input, which also takes the data pointer below its starting cell.
+    let reverse = Program::compile("<,[<,]>[.>]").unwrap();
+    assert_eq!(reverse.run(b"Cranelift"), b"tfilenarC");
+
+    assert!(Program::compile("[[]").is_err());
+    assert!(Program::compile("[]]").is_err());
+}
+
+#[test]
+fn run_main() {
+    main();
+}
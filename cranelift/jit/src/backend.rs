@@ -18,7 +18,8 @@ use std::ffi::CString;
 use std::io::Write;
 use std::ptr;
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use target_lexicon::PointerWidth;
 
 const WRITABLE_DATA_ALIGNMENT: u64 = 0x8;
@@ -169,11 +170,100 @@ pub struct JITModule {
     compiled_data_objects: SecondaryMap<DataId, Option<CompiledBlob>>,
     functions_to_finalize: Vec<FuncId>,
     data_objects_to_finalize: Vec<DataId>,
+    /// Code ranges of finalized functions, shared with `FunctionLookup` handles.
+    function_ranges: Arc<FunctionRanges>,
 
     /// Updates to the GOT awaiting relocations to be made and region protections to be set
     pending_got_updates: Vec<GotUpdate>,
 }
 
+/// The code range of a finalized function: its start and end address, and the function.
+type FunctionRange = (usize, usize, FuncId);
+
+/// The code ranges of finalized functions, published as immutable snapshots so that they can be
+/// read without locks while `finalize_definitions` publishes a new one.
+struct FunctionRanges {
+    /// The current snapshot, sorted by start address.
+    current: AtomicPtr<Vec<FunctionRange>>,
+    /// The number of lookups which may be reading a snapshot.
+    readers: AtomicUsize,
+    /// Replaced snapshots which lookups may still be reading. They are freed once no lookup is in
+    /// progress. Only `finalize_definitions` locks this. They stay boxed, since lookups may hold
+    /// pointers to them.
+    #[allow(clippy::vec_box)]
+    retired: Mutex<Vec<Box<Vec<FunctionRange>>>>,
+}
+
+impl FunctionRanges {
+    fn new() -> Self {
+        Self {
+            current: AtomicPtr::new(Box::into_raw(Box::new(Vec::new()))),
+            readers: AtomicUsize::new(0),
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn lookup(&self, addr: *const u8) -> Option<FuncId> {
+        let addr = addr as usize;
+        self.readers.fetch_add(1, Ordering::SeqCst);
+        // Safety: a snapshot is only freed when it is no longer current and no lookup is in
+        // progress, and `readers` was incremented before loading it.
+        let ranges = unsafe { &*self.current.load(Ordering::SeqCst) };
+        let index = ranges.partition_point(|&(start, _, _)| start <= addr);
+        let func_id = match index.checked_sub(1).map(|i| ranges[i]) {
+            Some((_, end, func_id)) if addr < end => Some(func_id),
+            _ => None,
+        };
+        self.readers.fetch_sub(1, Ordering::SeqCst);
+        func_id
+    }
+
+    /// Publish a snapshot which also contains `new_ranges`.
+    fn extend(&self, new_ranges: impl IntoIterator<Item = FunctionRange>) {
+        let mut retired = self.retired.lock().unwrap();
+        // Safety: only this function replaces the current snapshot, and `retired` is locked.
+        let mut ranges = unsafe { &*self.current.load(Ordering::SeqCst) }.clone();
+        ranges.extend(new_ranges);
+        ranges.sort_unstable_by_key(|&(start, _, _)| start);
+        let old = self
+            .current
+            .swap(Box::into_raw(Box::new(ranges)), Ordering::SeqCst);
+        // Safety: `old` came from `Box::into_raw` and is no longer reachable through `current`.
+        retired.push(unsafe { Box::from_raw(old) });
+        // Lookups which start from now on see the new snapshot, so once there are no readers the
+        // retired ones are unreachable.
+        if self.readers.load(Ordering::SeqCst) == 0 {
+            retired.clear();
+        }
+    }
+}
+
+impl Drop for FunctionRanges {
+    fn drop(&mut self) {
+        // Safety: `current` came from `Box::into_raw`, and nothing can be reading it any more.
+        drop(unsafe { Box::from_raw(*self.current.get_mut()) });
+    }
+}
+
+/// A handle for finding the function containing a code address, which can be used without access
+/// to the `JITModule`. It is obtained from [`JITModule::function_lookup`].
+///
+/// Lookups neither allocate nor take locks, so they can be done from a signal handler or a
+/// sampling profiler, concurrently with the module finalizing more functions.
+#[derive(Clone)]
+pub struct FunctionLookup(Arc<FunctionRanges>);
+
+impl FunctionLookup {
+    /// Returns the finalized function whose code contains `addr`, if any.
+    ///
+    /// Old bodies of functions redefined through [`JITModule::prepare_for_function_redefine`] are
+    /// never freed, so they keep mapping to their function. The result is meaningless once the
+    /// module's memory has been freed.
+    pub fn lookup(&self, addr: *const u8) -> Option<FuncId> {
+        self.0.lookup(addr)
+    }
+}
+
 /// A handle to allow freeing memory allocated by the `Module`.
 struct MemoryHandle {
     code: Memory,
@@ -404,6 +494,19 @@ impl JITModule {
         (compiled.ptr, compiled.size)
     }
 
+    /// Returns the finalized function whose code contains `addr`, if any.
+    ///
+    /// See [`FunctionLookup::lookup`], which does the same without access to the module.
+    pub fn lookup_function(&self, addr: *const u8) -> Option<FuncId> {
+        self.function_ranges.lookup(addr)
+    }
+
+    /// Returns a handle for finding the function containing a code address, which stays up to
+    /// date as more functions are finalized.
+    pub fn function_lookup(&self) -> FunctionLookup {
+        FunctionLookup(self.function_ranges.clone())
+    }
+
     fn record_function_for_perf(&self, ptr: *mut u8, size: usize, name: &str) {
         // The Linux perf tool supports JIT code via a /tmp/perf-$PID.map file,
         // which contains memory regions and their associated names.  If we
@@ -428,10 +531,11 @@ impl JITModule {
     /// Use `get_finalized_function` and `get_finalized_data` to obtain the final
     /// artifacts.
     pub fn finalize_definitions(&mut self) {
-        for func in std::mem::take(&mut self.functions_to_finalize) {
-            let decl = self.declarations.get_function_decl(func);
+        let mut new_ranges = Vec::new();
+        for func_id in std::mem::take(&mut self.functions_to_finalize) {
+            let decl = self.declarations.get_function_decl(func_id);
             assert!(decl.linkage.is_definable());
-            let func = self.compiled_functions[func]
+            let func = self.compiled_functions[func_id]
                 .as_ref()
                 .expect("function must be compiled before it can be finalized");
             func.perform_relocations(
@@ -439,6 +543,11 @@ impl JITModule {
                 |name| self.get_got_address(name).as_ptr().cast(),
                 |name| self.get_plt_address(name),
             );
+            let start = func.ptr as usize;
+            new_ranges.push((start, start + func.size, func_id));
+        }
+        if !new_ranges.is_empty() {
+            self.function_ranges.extend(new_ranges);
         }
 
         for data in std::mem::take(&mut self.data_objects_to_finalize) {
@@ -508,6 +617,7 @@ impl JITModule {
             compiled_data_objects: SecondaryMap::new(),
             functions_to_finalize: Vec::new(),
             data_objects_to_finalize: Vec::new(),
+            function_ranges: Arc::new(FunctionRanges::new()),
             pending_got_updates: Vec::new(),
         };
 
@@ -707,18 +817,15 @@ impl Module for JITModule {
             .allocate(size, align)
             .expect("TODO: handle OOM etc.");
 
-        {
-            let mem = unsafe { std::slice::from_raw_parts_mut(ptr, size) };
-            mem.copy_from_slice(compiled_code.code_buffer());
+        let mut relocs = Vec::new();
+        unsafe {
+            compiled_code.emit_to_memory(
+                ptr,
+                |reloc| relocs.push(ModuleReloc::from_mach_reloc(reloc, &ctx.func)),
+                |_trap| {},
+            );
         }
 
-        let relocs = compiled_code
-            .buffer
-            .relocs()
-            .iter()
-            .map(|reloc| ModuleReloc::from_mach_reloc(reloc, &ctx.func))
-            .collect();
-
         self.record_function_for_perf(ptr, size, &decl.name);
         self.compiled_functions[id] = Some(CompiledBlob { ptr, size, relocs });
 
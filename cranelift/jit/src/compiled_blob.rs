@@ -102,8 +102,63 @@ impl CompiledBlob {
                         iptr.write_unaligned(ins);
                     }
                 }
+                Reloc::RiscvCall => {
+                    let base = get_address(name);
+                    let what = unsafe { base.offset(isize::try_from(addend).unwrap()) };
+                    let pcrel = (what as isize - at as isize) as i64;
+                    // The call is an `auipc` + `jalr` pair. The `jalr` immediate is
+                    // sign-extended, so round the upper 20 bits to compensate for a
+                    // negative lower 12 bits.
+                    let hi20 = (pcrel + 0x800) & !0xfff;
+                    let lo12 = pcrel - hi20;
+                    let hi20 = i32::try_from(hi20).unwrap() as u32;
+                    let lo12 = (lo12 as u32) & 0xfff;
+                    let auipc = at as *mut u32;
+                    let jalr = unsafe { auipc.add(1) };
+                    unsafe {
+                        auipc.write_unaligned(auipc.read_unaligned() | hi20);
+                        jalr.write_unaligned(jalr.read_unaligned() | (lo12 << 20));
+                    }
+                }
                 _ => unimplemented!(),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn riscv_call() {
+        // auipc ra, 0; jalr ra, 0(ra)
+        let code = [0x00000097u32, 0x000080e7];
+        for target in [0x1234i64, 0xfffi64, -0x800, -0x12345678, 0x7fff_f7ff] {
+            let mut bytes = Vec::new();
+            for insn in code {
+                bytes.extend_from_slice(&insn.to_le_bytes());
+            }
+            let blob = CompiledBlob {
+                ptr: bytes.as_mut_ptr(),
+                size: bytes.len(),
+                relocs: vec![ModuleReloc {
+                    kind: Reloc::RiscvCall,
+                    offset: 0,
+                    name: ModuleExtName::user(0, 0),
+                    addend: target,
+                }],
+            };
+            let ptr = blob.ptr as *const u8;
+            blob.perform_relocations(|_| ptr, |_| unreachable!(), |_| unreachable!());
+
+            let auipc = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+            let jalr = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+            let hi = (auipc & 0xfffff000) as i32 as i64;
+            let lo = (jalr as i32 >> 20) as i64;
+            assert_eq!(hi + lo, target);
+            assert_eq!(auipc & 0xfff, code[0]);
+            assert_eq!(jalr & 0xfffff, code[1]);
+        }
+    }
+}
@@ -30,7 +30,7 @@ mod backend;
 mod compiled_blob;
 mod memory;
 
-pub use crate::backend::{JITBuilder, JITModule};
+pub use crate::backend::{FunctionLookup, JITBuilder, JITModule};
 
 /// Version number of this crate.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
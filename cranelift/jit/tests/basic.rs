@@ -25,6 +25,7 @@ fn error_on_incompatible_sig_in_declare_function() {
         params: vec![AbiParam::new(types::I64)],
         returns: vec![],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
     module
         .declare_function("abc", Linkage::Local, &sig)
@@ -41,6 +42,7 @@ fn define_simple_function(module: &mut JITModule) -> FuncId {
         params: vec![],
         returns: vec![],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
 
     let func_id = module
@@ -81,6 +83,38 @@ fn panic_on_define_after_finalize() {
     define_simple_function(&mut module);
 }
 
+#[test]
+fn lookup_function_by_address() {
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    // FIXME set back to true once the x64 backend supports it.
+    flag_builder.set("is_pic", "false").unwrap();
+    let isa_builder = cranelift_native::builder().unwrap_or_else(|msg| {
+        panic!("host machine is not supported: {}", msg);
+    });
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    let lookup = module.function_lookup();
+    let func_id = define_simple_function(&mut module);
+    assert_eq!(module.lookup_function(std::ptr::null()), None);
+
+    module.finalize_definitions();
+    let code = module.get_finalized_function(func_id);
+    assert_eq!(module.lookup_function(code), Some(func_id));
+    assert_eq!(module.lookup_function(code.wrapping_add(1)), Some(func_id));
+    assert_eq!(module.lookup_function(code.wrapping_sub(1)), None);
+
+    // The handle sees functions finalized after it was created, from any thread.
+    let code = code as usize;
+    let found = std::thread::spawn(move || lookup.lookup(code as *const u8))
+        .join()
+        .unwrap();
+    assert_eq!(found, Some(func_id));
+}
+
 #[test]
 fn switch_error() {
     use cranelift_codegen::settings;
@@ -89,6 +123,7 @@ fn switch_error() {
         params: vec![AbiParam::new(types::I32)],
         returns: vec![AbiParam::new(types::I32)],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
 
     let mut func = Function::with_name_signature(UserFuncName::default(), sig);
@@ -172,6 +207,7 @@ fn libcall_function() {
         params: vec![],
         returns: vec![],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
 
     let func_id = module
@@ -211,3 +247,78 @@ fn libcall_function() {
 
     module.finalize_definitions();
 }
+
+// Variadic calls are only implemented for the System V calling convention on x86-64.
+#[cfg(all(target_arch = "x86_64", unix))]
+#[test]
+fn variadic_libc_call() {
+    use std::ffi::CString;
+    use std::os::raw::c_char;
+
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    // FIXME set back to true once the x64 backend supports it.
+    flag_builder.set("is_pic", "false").unwrap();
+    let isa_builder = cranelift_native::builder().unwrap_or_else(|msg| {
+        panic!("host machine is not supported: {}", msg);
+    });
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    // fn format(buf: *mut u8, len: usize, format: *const c_char, x: f64) -> i32
+    let sig = Signature {
+        params: vec![
+            AbiParam::new(types::I64),
+            AbiParam::new(types::I64),
+            AbiParam::new(types::I64),
+            AbiParam::new(types::F64),
+        ],
+        returns: vec![AbiParam::new(types::I32)],
+        call_conv: CallConv::SystemV,
+        variadic: None,
+    };
+    let func_id = module
+        .declare_function("format", Linkage::Local, &sig)
+        .unwrap();
+
+    // int snprintf(char *buf, size_t len, const char *format, ...)
+    let mut snprintf_sig = sig.clone();
+    snprintf_sig.variadic = Some(3);
+    let snprintf = module
+        .declare_function("snprintf", Linkage::Import, &snprintf_sig)
+        .unwrap();
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let args = bcx.block_params(block).to_vec();
+        let callee = module.declare_func_in_func(snprintf, bcx.func);
+        let call = bcx.ins().call(callee, &args);
+        let written = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[written]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+    module.finalize_definitions();
+
+    let code = module.get_finalized_function(func_id);
+    let format = unsafe {
+        std::mem::transmute::<*const u8, extern "C" fn(*mut u8, usize, *const c_char, f64) -> i32>(
+            code,
+        )
+    };
+    // `snprintf` only finds the `f64` if `%al` counts the vector register it is passed in.
+    let mut buf = [0u8; 16];
+    let fmt = CString::new("%.2f").unwrap();
+    let written = format(buf.as_mut_ptr(), buf.len(), fmt.as_ptr(), 2.5);
+    assert_eq!(written, 4);
+    assert_eq!(&buf[..5], b"2.50\0");
+}
@@ -32,6 +32,7 @@ pub struct ObjectBuilder {
     endian: object::Endianness,
     name: Vec<u8>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
+    symbol_names: Box<dyn Fn(&str) -> String + Send + Sync>,
     per_function_section: bool,
 }
 
@@ -92,6 +93,7 @@ impl ObjectBuilder {
             endian,
             name: name.into(),
             libcall_names,
+            symbol_names: Box::new(|name| name.to_owned()),
             per_function_section: false,
         })
     }
@@ -101,6 +103,21 @@ impl ObjectBuilder {
         self.per_function_section = per_function_section;
         self
     }
+
+    /// Set the function that maps the names passed to `declare_function` and `declare_data` to
+    /// the names of the symbols written to the object file, for example to add a prefix that
+    /// keeps them from colliding with host symbols. By default names are used unchanged.
+    ///
+    /// The `Module` API keeps using the original names. Libcalls and anonymous functions and data
+    /// objects are not renamed. The object format's own mangling, such as the leading underscore
+    /// on Mach-O, is still applied on top of the returned name.
+    pub fn symbol_names(
+        &mut self,
+        symbol_names: Box<dyn Fn(&str) -> String + Send + Sync>,
+    ) -> &mut Self {
+        self.symbol_names = symbol_names;
+        self
+    }
 }
 
 /// An `ObjectModule` implements `Module` and emits ".o" files using the `object` library.
@@ -115,6 +132,7 @@ pub struct ObjectModule {
     relocs: Vec<SymbolRelocs>,
     libcalls: HashMap<ir::LibCall, SymbolId>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
+    symbol_names: Box<dyn Fn(&str) -> String + Send + Sync>,
     known_symbols: HashMap<ir::KnownSymbol, SymbolId>,
     per_function_section: bool,
     anon_func_number: u64,
@@ -135,6 +153,7 @@ impl ObjectModule {
             relocs: Vec::new(),
             libcalls: HashMap::new(),
             libcall_names: builder.libcall_names,
+            symbol_names: builder.symbol_names,
             known_symbols: HashMap::new(),
             per_function_section: builder.per_function_section,
             anon_func_number: 0,
@@ -170,7 +189,8 @@ impl Module for ObjectModule {
         linkage: Linkage,
         signature: &ir::Signature,
     ) -> ModuleResult<FuncId> {
-        validate_symbol(name)?;
+        let symbol_name = (self.symbol_names)(name);
+        validate_symbol(&symbol_name)?;
 
         let (id, linkage) = self
             .declarations
@@ -184,7 +204,7 @@ impl Module for ObjectModule {
             symbol.weak = weak;
         } else {
             let symbol_id = self.object.add_symbol(Symbol {
-                name: name.as_bytes().to_vec(),
+                name: symbol_name.into_bytes(),
                 value: 0,
                 size: 0,
                 kind: SymbolKind::Text,
@@ -230,7 +250,8 @@ impl Module for ObjectModule {
         writable: bool,
         tls: bool,
     ) -> ModuleResult<DataId> {
-        validate_symbol(name)?;
+        let symbol_name = (self.symbol_names)(name);
+        validate_symbol(&symbol_name)?;
 
         let (id, linkage) = self
             .declarations
@@ -252,7 +273,7 @@ impl Module for ObjectModule {
             symbol.weak = weak;
         } else {
             let symbol_id = self.object.add_symbol(Symbol {
-                name: name.as_bytes().to_vec(),
+                name: symbol_name.into_bytes(),
                 value: 0,
                 size: 0,
                 kind,
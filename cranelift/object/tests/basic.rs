@@ -20,6 +20,7 @@ fn error_on_incompatible_sig_in_declare_function() {
         params: vec![AbiParam::new(types::I64)],
         returns: vec![],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
     module
         .declare_function("abc", Linkage::Local, &sig)
@@ -36,6 +37,7 @@ fn define_simple_function(module: &mut ObjectModule) -> FuncId {
         params: vec![],
         returns: vec![],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
 
     let func_id = module
@@ -80,6 +82,7 @@ fn switch_error() {
         params: vec![AbiParam::new(types::I32)],
         returns: vec![AbiParam::new(types::I32)],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
 
     let mut func = Function::with_name_signature(UserFuncName::default(), sig);
@@ -158,6 +161,7 @@ fn libcall_function() {
         params: vec![],
         returns: vec![],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
 
     let func_id = module
@@ -212,6 +216,7 @@ fn reject_nul_byte_symbol_for_func() {
         params: vec![],
         returns: vec![],
         call_conv: CallConv::SystemV,
+        variadic: None,
     };
 
     let _ = module
@@ -239,3 +244,34 @@ fn reject_nul_byte_symbol_for_data() {
         )
         .unwrap();
 }
+
+#[test]
+fn custom_symbol_names() {
+    let flag_builder = settings::builder();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name("x86_64-unknown-linux-gnu").unwrap();
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut builder = ObjectBuilder::new(isa, "foo", default_libcall_names()).unwrap();
+    builder.symbol_names(Box::new(|name| format!("mymod_{}", name)));
+    let mut module = ObjectModule::new(builder);
+
+    let func_id = define_simple_function(&mut module);
+    let data_id = module
+        .declare_data(
+            "data",
+            Linkage::Import,
+            /* writable = */ false,
+            /* tls = */ false,
+        )
+        .unwrap();
+
+    // The module API still uses the names the symbols were declared with.
+    assert_eq!(module.get_name("abc"), Some(FuncOrDataId::Func(func_id)));
+
+    let product = module.finish();
+    let func_symbol = product.object.symbol(product.function_symbol(func_id));
+    assert_eq!(func_symbol.name, b"mymod_abc");
+    let data_symbol = product.object.symbol(product.data_symbol(data_id));
+    assert_eq!(data_symbol.name, b"mymod_data");
+}
@@ -26,6 +26,7 @@ pub enum Token<'a> {
     Multiply,              // '*'
     Comma,                 // ','
     Dot,                   // '.'
+    Ellipsis,              // '...'
     Colon,                 // ':'
     Equal,                 // '='
     Not,                   // '!'
@@ -484,7 +485,13 @@ impl<'a> Lexer<'a> {
                 Some('[') => Some(self.scan_char(Token::LBracket)),
                 Some(']') => Some(self.scan_char(Token::RBracket)),
                 Some(',') => Some(self.scan_char(Token::Comma)),
-                Some('.') => Some(self.scan_char(Token::Dot)),
+                Some('.') => {
+                    if self.looking_at("...") {
+                        Some(self.scan_chars(3, Token::Ellipsis))
+                    } else {
+                        Some(self.scan_char(Token::Dot))
+                    }
+                }
                 Some(':') => Some(self.scan_char(Token::Colon)),
                 Some('=') => Some(self.scan_char(Token::Equal)),
                 Some('!') => Some(self.scan_char(Token::Not)),
@@ -595,7 +602,7 @@ mod tests {
 
     #[test]
     fn lex_chars() {
-        let mut lex = Lexer::new("(); hello\n = :{, }.");
+        let mut lex = Lexer::new("(); hello\n = :{, }. ...");
         assert_eq!(lex.next(), token(Token::LPar, 1));
         assert_eq!(lex.next(), token(Token::RPar, 1));
         assert_eq!(lex.next(), token(Token::Comment("; hello"), 1));
@@ -605,6 +612,7 @@ mod tests {
         assert_eq!(lex.next(), token(Token::Comma, 2));
         assert_eq!(lex.next(), token(Token::RBrace, 2));
         assert_eq!(lex.next(), token(Token::Dot, 2));
+        assert_eq!(lex.next(), token(Token::Ellipsis, 2));
         assert_eq!(lex.next(), None);
     }
 
@@ -80,6 +80,13 @@ macro_rules! match_imm {
 /// After some quick benchmarks a program should never have more than 100,000 blocks.
 const MAX_BLOCKS_IN_A_FUNCTION: u32 = 100_000;
 
+/// Default limit on the number of values in a function.
+const MAX_VALUES_IN_A_FUNCTION: u32 = 1_000_000;
+
+/// Default limit on the number of instructions in a function. There is none: unlike block and
+/// value numbers, every instruction needs its own text, so the input size already bounds them.
+const MAX_INSTS_IN_A_FUNCTION: u32 = u32::MAX;
+
 /// Parse the entire `text` into a list of functions.
 ///
 /// Any test commands or target declarations are ignored.
@@ -99,6 +106,8 @@ pub struct ParseOptions<'a> {
     pub default_calling_convention: CallConv,
     /// Default for unwind-info setting (enabled or disabled).
     pub unwind_info: bool,
+    /// Limits on the size of the parsed functions.
+    pub limits: ParseLimits,
 }
 
 impl Default for ParseOptions<'_> {
@@ -108,6 +117,35 @@ impl Default for ParseOptions<'_> {
             target: None,
             default_calling_convention: CallConv::Fast,
             unwind_info: false,
+            limits: ParseLimits::default(),
+        }
+    }
+}
+
+/// Limits on the size of the functions accepted by the parser.
+///
+/// Entity numbers in the text format determine how many entities get allocated, so a single
+/// reference like `block4000000000` could otherwise exhaust memory. The limits are checked before
+/// allocating anything for an entity, which makes it safe to parse untrusted input.
+///
+/// By default, functions may have up to 100,000 blocks and 1,000,000 values, and any number of
+/// instructions. Block and value numbers at or above those limits are rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum number of blocks in a function; block numbers must be below this.
+    pub max_blocks: u32,
+    /// Maximum number of values in a function; value numbers must be below this.
+    pub max_values: u32,
+    /// Maximum number of instructions in a function.
+    pub max_insts: u32,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_blocks: MAX_BLOCKS_IN_A_FUNCTION,
+            max_values: MAX_VALUES_IN_A_FUNCTION,
+            max_insts: MAX_INSTS_IN_A_FUNCTION,
         }
     }
 }
@@ -117,7 +155,7 @@ impl Default for ParseOptions<'_> {
 /// The returned `TestFile` contains direct references to substrings of `text`.
 pub fn parse_test<'a>(text: &'a str, options: ParseOptions<'a>) -> ParseResult<TestFile<'a>> {
     let _tt = timing::parse_text();
-    let mut parser = Parser::new(text);
+    let mut parser = Parser::new(text).with_limits(options.limits);
 
     // Gather the preamble comments.
     parser.start_gathering_comments();
@@ -233,6 +271,9 @@ pub struct Parser<'a> {
 
     /// Default calling conventions; used when none is specified.
     default_calling_convention: CallConv,
+
+    /// Limits on the size of parsed functions.
+    limits: ParseLimits,
 }
 
 /// Context for resolving references when parsing a single function.
@@ -515,6 +556,7 @@ impl<'a> Parser<'a> {
             comments: Vec::new(),
             default_calling_convention: CallConv::Fast,
             predeclared_external_names: Default::default(),
+            limits: ParseLimits::default(),
         }
     }
 
@@ -527,6 +569,20 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Modify the limits on the size of parsed functions; returns a new parser with the changed
+    /// limits.
+    pub fn with_limits(self, limits: ParseLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Check that `value` is below the configured limit, before any space is allocated for it.
+    fn check_value_limit(&self, value: Value) -> ParseResult<()> {
+        if value.as_u32() >= self.limits.max_values {
+            return err!(self.loc, "too many values");
+        }
+        Ok(())
+    }
+
     // Consume the current lookahead token and return it.
     fn consume(&mut self) -> Token<'a> {
         self.lookahead.take().expect("No token to consume")
@@ -1443,9 +1499,11 @@ impl<'a> Parser<'a> {
         let mut sig = Signature::new(self.default_calling_convention);
 
         self.match_token(Token::LPar, "expected function signature: ( args... )")?;
-        // signature ::=  "(" * [abi-param-list] ")" ["->" retlist] [callconv]
+        // signature ::=  "(" * [param-list] ")" ["->" retlist] [callconv]
         if self.token() != Some(Token::RPar) {
-            sig.params = self.parse_abi_param_list()?;
+            let (params, variadic) = self.parse_param_list()?;
+            sig.params = params;
+            sig.variadic = variadic;
         }
         self.match_token(Token::RPar, "expected ')' after function arguments")?;
         if self.optional(Token::Arrow) {
@@ -1466,6 +1524,33 @@ impl<'a> Parser<'a> {
         Ok(sig)
     }
 
+    // Parse the list of function parameters, which may mark where the variadic arguments start.
+    // Returns the parameters and the number of fixed parameters, if there is a marker.
+    //
+    // param-list ::= * param-item { "," param-item }
+    // param-item ::= abi-param | "..."
+    //
+    fn parse_param_list(&mut self) -> ParseResult<(Vec<AbiParam>, Option<usize>)> {
+        let mut list = Vec::new();
+        let mut variadic = None;
+
+        loop {
+            if self.optional(Token::Ellipsis) {
+                if variadic.is_some() {
+                    return err!(self.loc, "a signature can only have one '...'");
+                }
+                variadic = Some(list.len());
+            } else {
+                list.push(self.parse_abi_param()?);
+            }
+            if !self.optional(Token::Comma) {
+                break;
+            }
+        }
+
+        Ok((list, variadic))
+    }
+
     // Parse list of function parameter / return value types.
     //
     // paramlist ::= * param { "," param }
@@ -2093,12 +2178,12 @@ impl<'a> Parser<'a> {
         self.start_gathering_comments();
 
         let block_num = self.match_block("expected block header")?;
-        let block = ctx.add_block(block_num, self.loc)?;
-
-        if block_num.as_u32() >= MAX_BLOCKS_IN_A_FUNCTION {
+        if block_num.as_u32() >= self.limits.max_blocks {
             return Err(self.error("too many blocks"));
         }
 
+        let block = ctx.add_block(block_num, self.loc)?;
+
         if self.token() == Some(Token::LPar) {
             self.parse_block_params(ctx, block)?;
         }
@@ -2130,6 +2215,7 @@ impl<'a> Parser<'a> {
             let results = self.parse_inst_results()?;
 
             for result in &results {
+                self.check_value_limit(*result)?;
                 while ctx.function.dfg.num_values() <= result.index() {
                     ctx.function.dfg.make_invalid_value_for_parser();
                 }
@@ -2188,6 +2274,7 @@ impl<'a> Parser<'a> {
         self.match_token(Token::Colon, "expected ':' after block argument")?;
         // block-param ::= Value(v) ":" * Type(t) arg-loc?
 
+        self.check_value_limit(v)?;
         while ctx.function.dfg.num_values() <= v.index() {
             ctx.function.dfg.make_invalid_value_for_parser();
         }
@@ -2282,6 +2369,10 @@ impl<'a> Parser<'a> {
             ctx.map.def_value(*val, self.loc)?;
         }
 
+        if ctx.function.dfg.num_insts() >= self.limits.max_insts as usize {
+            return err!(self.loc, "too many instructions");
+        }
+
         // Collect comments for the next instruction.
         self.start_gathering_comments();
 
@@ -2552,6 +2643,7 @@ impl<'a> Parser<'a> {
     /// Parse a CLIF run command.
     ///
     /// run-command ::= "run" [":" invocation comparison expected]
+    ///               \ "run" ":" invocation "==" "trap"
     ///               \ "print" [":" invocation]
     fn parse_run_command(&mut self, sig: &Signature) -> ParseResult<RunCommand> {
         // skip semicolon
@@ -2561,6 +2653,13 @@ impl<'a> Parser<'a> {
                 if self.optional(Token::Colon) {
                     let invocation = self.parse_run_invocation(sig)?;
                     let comparison = self.parse_run_comparison()?;
+                    if self.token() == Some(Token::Identifier("trap")) {
+                        self.consume();
+                        if comparison != Comparison::Equals {
+                            return Err(self.error("a trap can only be expected with =="));
+                        }
+                        return Ok(RunCommand::Trap(invocation));
+                    }
                     let expected = self.parse_run_returns(sig)?;
                     Ok(RunCommand::Run(invocation, comparison, expected))
                 } else if sig.params.is_empty()
@@ -3249,6 +3348,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn limits() {
+        let limits = ParseLimits {
+            max_blocks: 2,
+            max_values: 4,
+            max_insts: 2,
+        };
+        let parse = |text| {
+            Parser::new(text)
+                .with_limits(limits)
+                .parse_function()
+                .map(|_| ())
+                .map_err(|e| e.message)
+        };
+
+        assert_eq!(
+            parse("function %f() { block1: v3 = iconst.i32 0 return }"),
+            Ok(())
+        );
+        assert_eq!(
+            parse("function %f() { block4000000000: return }"),
+            Err("too many blocks".to_string())
+        );
+        assert_eq!(
+            parse("function %f() { block0: v5 = iconst.i32 0 return }"),
+            Err("too many values".to_string())
+        );
+        assert_eq!(
+            parse("function %f() { block0(v5: i32): return }"),
+            Err("too many values".to_string())
+        );
+        assert_eq!(
+            parse("function %f() { block0: nop nop return }"),
+            Err("too many instructions".to_string())
+        );
+    }
+
     #[test]
     fn signature() {
         let sig = Parser::new("()system_v").parse_signature().unwrap();
@@ -3264,6 +3400,25 @@ mod tests {
             "(i8 uext, f32, f64, i32 sret) -> i32 sext, f64 system_v"
         );
         assert_eq!(sig2.call_conv, CallConv::SystemV);
+        assert_eq!(sig2.variadic, None);
+
+        let sig3 = Parser::new("(i64, ..., f64, i32) -> i32 system_v")
+            .parse_signature()
+            .unwrap();
+        assert_eq!(sig3.params.len(), 3);
+        assert_eq!(sig3.variadic, Some(1));
+        assert_eq!(sig3.to_string(), "(i64, ..., f64, i32) -> i32 system_v");
+        assert_eq!(
+            Parser::new("(...)").parse_signature().unwrap().variadic,
+            Some(0)
+        );
+        assert_eq!(
+            Parser::new("(i64, ..., ...)")
+                .parse_signature()
+                .unwrap_err()
+                .to_string(),
+            "1: a signature can only have one '...'"
+        );
 
         // Old-style signature without a calling convention.
         assert_eq!(
@@ -3871,6 +4026,7 @@ mod tests {
             "run: %my_func(true) == 0x0f0e0d0c0b0a09080706050403020100",
             &sig(&[B32], &[I8X16]),
         );
+        assert_roundtrip("run: %fn0(8) == trap", &sig(&[I32], &[I32]));
 
         // Verify that default invocations are created when not specified.
         assert_eq!(
@@ -3889,6 +4045,7 @@ mod tests {
         assert!(parse("run", &sig(&[], &[I32])).is_err());
         assert!(parse("print:", &sig(&[], &[])).is_err());
         assert!(parse("run: ", &sig(&[], &[])).is_err());
+        assert!(parse("run: %fn0() != trap", &sig(&[], &[I32])).is_err());
     }
 
     #[test]
@@ -5,6 +5,7 @@
 //!
 //! - `; run`: this assumes the function has a signature like `() -> b*`.
 //! - `; run: %fn(42, 4.2) == false`: this syntax specifies the parameters and return values.
+//! - `; run: %fn(42) == trap`: this syntax specifies that the function traps.
 
 use cranelift_codegen::data_value::{self, DataValue, DisplayDataValues};
 use std::fmt::{self, Display, Formatter};
@@ -18,6 +19,17 @@ pub enum RunCommand {
     Print(Invocation),
     /// Invoke a function and compare its result to a value sequence.
     Run(Invocation, Comparison, Vec<DataValue>),
+    /// Invoke a function and check that it traps.
+    Trap(Invocation),
+}
+
+/// How the invocation of a function by a [RunCommand] ended.
+#[derive(PartialEq, Debug)]
+pub enum Outcome {
+    /// The function returned these values.
+    Return(Vec<DataValue>),
+    /// The function trapped, for the described reason.
+    Trap(String),
 }
 
 impl RunCommand {
@@ -25,20 +37,26 @@ impl RunCommand {
     ///  - for [RunCommand::Print], print the returned values from invoking the function.
     ///  - for [RunCommand::Run], compare the returned values from the invoked function and
     ///    return an `Err` with a descriptive string if the comparison fails.
+    ///  - for [RunCommand::Trap], return an `Err` if the invoked function doesn't trap.
     ///
     /// Accepts a function used for invoking the actual execution of the command. This function,
     /// `invoked_fn`, is passed the _function name_ and _function arguments_ of the [Invocation].
     pub fn run<F>(&self, invoke_fn: F) -> Result<(), String>
     where
-        F: FnOnce(&str, &[DataValue]) -> Result<Vec<DataValue>, String>,
+        F: FnOnce(&str, &[DataValue]) -> Result<Outcome, String>,
     {
-        match self {
-            RunCommand::Print(invoke) => {
-                let actual = invoke_fn(&invoke.func, &invoke.args)?;
+        let invoke = self.invocation();
+        let outcome = invoke_fn(&invoke.func, &invoke.args)?;
+        match (self, outcome) {
+            (RunCommand::Trap(_), Outcome::Trap(_)) => {}
+            (RunCommand::Trap(_), Outcome::Return(_)) => {
+                return Err(format!("Failed test: {}, but the function returned", self));
+            }
+            (_, Outcome::Trap(trap)) => return Err(format!("unexpected trap: {}", trap)),
+            (RunCommand::Print(invoke), Outcome::Return(actual)) => {
                 println!("{} -> {}", invoke, DisplayDataValues(&actual))
             }
-            RunCommand::Run(invoke, compare, expected) => {
-                let actual = invoke_fn(&invoke.func, &invoke.args)?;
+            (RunCommand::Run(_, compare, expected), Outcome::Return(actual)) => {
                 let matched = Self::compare_results(compare, &actual, expected);
                 if !matched {
                     let actual = DisplayDataValues(&actual);
@@ -49,6 +67,15 @@ impl RunCommand {
         Ok(())
     }
 
+    /// The invocation of the function this command runs.
+    pub fn invocation(&self) -> &Invocation {
+        match self {
+            RunCommand::Print(invoke) | RunCommand::Run(invoke, ..) | RunCommand::Trap(invoke) => {
+                invoke
+            }
+        }
+    }
+
     fn compare_results(
         compare: &Comparison,
         actual: &Vec<DataValue>,
@@ -75,6 +102,7 @@ impl Display for RunCommand {
                 let expected = DisplayDataValues(expected);
                 write!(f, "run: {} {} {}", invocation, comparison, expected)
             }
+            RunCommand::Trap(invocation) => write!(f, "run: {} == trap", invocation),
         }
     }
 }
@@ -136,7 +164,16 @@ mod test {
             .unwrap()
             .unwrap();
 
-        assert!(command.run(|_, _| Ok(vec![DataValue::I32(42)])).is_ok());
-        assert!(command.run(|_, _| Ok(vec![DataValue::I32(43)])).is_err());
+        let returns = |value| move |_: &str, _: &[DataValue]| Ok(Outcome::Return(vec![value]));
+        let traps = |_: &str, _: &[DataValue]| Ok(Outcome::Trap("heap_oob".to_string()));
+        assert!(command.run(returns(DataValue::I32(42))).is_ok());
+        assert!(command.run(returns(DataValue::I32(43))).is_err());
+        assert!(command.run(traps).is_err());
+
+        let command = parse_run_command(";; run: %return42() == trap ", &signature)
+            .unwrap()
+            .unwrap();
+        assert!(command.run(traps).is_ok());
+        assert!(command.run(returns(DataValue::I32(42))).is_err());
     }
 }
@@ -27,6 +27,9 @@ mod utils;
 #[cfg(feature = "souper-harvest")]
 mod souper_harvest;
 
+#[cfg(feature = "serve")]
+mod serve;
+
 #[cfg(feature = "wasm")]
 mod wasm;
 
@@ -51,6 +54,11 @@ enum Commands {
     SouperHarvest(souper_harvest::Options),
     #[cfg(not(feature = "souper-harvest"))]
     SouperHarvest(CompiledWithoutSupportOptions),
+
+    #[cfg(feature = "serve")]
+    Serve(serve::Options),
+    #[cfg(not(feature = "serve"))]
+    Serve(CompiledWithoutSupportOptions),
 }
 
 /// Run Cranelift tests
@@ -119,6 +127,13 @@ fn main() -> anyhow::Result<()> {
              subcommand",
         ),
 
+        #[cfg(feature = "serve")]
+        Commands::Serve(s) => serve::run(&s)?,
+        #[cfg(not(feature = "serve"))]
+        Commands::Serve(_) => anyhow::bail!(
+            "Error: clif-util was compiled without support for the `serve` subcommand",
+        ),
+
         Commands::Test(t) => {
             cranelift_filetests::run(
                 t.verbose,
@@ -0,0 +1,265 @@
+//! Compile Cranelift IR received over stdin and send the results back over stdout.
+//!
+//! This lets an embedder keep untrusted input out of the process that runs the generated code: it
+//! spawns `clif-util serve` (possibly sandboxed), writes requests to its stdin and reads the
+//! compiled functions from its stdout.
+//!
+//! All integers are little-endian. A request is a `u32` byte length followed by that many bytes of
+//! CLIF text, which may contain several functions. The reply to each request is a `u32` record
+//! count followed by that many records, one per function in source order. A record is a `u8`
+//! status, a `u32` byte length and the payload:
+//!
+//! - status 0: the payload is the compiled function, as produced by
+//!   `cranelift_codegen::incremental_cache::serialize_compiled`. The client turns it back into
+//!   `CompiledCode` with `incremental_cache::try_finish_recompile`, passing the same function it
+//!   sent, which also checks that both sides run the same Cranelift version.
+//! - status 1: the payload is a UTF-8 error message. If the request as a whole couldn't be parsed,
+//!   or is longer than `--max-request-size`, the reply is a single such record.
+//!
+//! The server exits when stdin is closed.
+
+use crate::utils::parse_sets_and_triple;
+use anyhow::{Context as _, Result};
+use clap::Parser;
+use cranelift_codegen::incremental_cache::serialize_compiled;
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::print_errors::pretty_error;
+use cranelift_codegen::Context;
+use std::io::{self, Read, Write};
+
+const STATUS_OK: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+
+/// Default for `--max-request-size`.
+const DEFAULT_MAX_REQUEST_SIZE: u32 = 64 << 20;
+
+/// Compile Cranelift IR sent over stdin, writing the results to stdout
+#[derive(Parser)]
+pub struct Options {
+    /// Configure Cranelift settings
+    #[clap(long = "set")]
+    settings: Vec<String>,
+
+    /// Specify the Cranelift target
+    #[clap(long = "target")]
+    target: String,
+
+    /// Reject requests longer than this many bytes
+    #[clap(long = "max-request-size", default_value_t = DEFAULT_MAX_REQUEST_SIZE)]
+    max_request_size: u32,
+}
+
+pub fn run(options: &Options) -> Result<()> {
+    let parsed = parse_sets_and_triple(&options.settings, &options.target)?;
+    let isa = match parsed.as_fisa().isa {
+        Some(isa) => isa,
+        None => anyhow::bail!("`serve` requires a target isa"),
+    };
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    serve(
+        isa,
+        options.max_request_size,
+        &mut stdin.lock(),
+        &mut io::BufWriter::new(stdout.lock()),
+    )
+}
+
+/// Answer the requests read from `input` until it is exhausted.
+fn serve(
+    isa: &dyn TargetIsa,
+    max_request_size: u32,
+    input: &mut impl Read,
+    output: &mut impl Write,
+) -> Result<()> {
+    while let Some(request) = read_request(input, max_request_size)? {
+        let records = match request.map(String::from_utf8) {
+            Ok(Ok(source)) => compile_request(isa, &source),
+            Ok(Err(err)) => vec![Err(format!("request is not valid UTF-8: {}", err))],
+            Err(message) => vec![Err(message)],
+        };
+        write_reply(output, &records).context("failed to write reply")?;
+    }
+
+    Ok(())
+}
+
+/// Read the next length-prefixed request, or `None` at end of input.
+///
+/// Requests longer than `max_size` are skipped without being buffered, and produce an error
+/// message instead.
+fn read_request(input: &mut impl Read, max_size: u32) -> Result<Option<Result<Vec<u8>, String>>> {
+    let mut len = [0; 4];
+    match input.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err).context("failed to read request length"),
+    }
+    let len = u32::from_le_bytes(len);
+    if len > max_size {
+        let skipped = io::copy(&mut input.take(len.into()), &mut io::sink())
+            .context("failed to read request")?;
+        if skipped < len.into() {
+            anyhow::bail!("failed to read request: unexpected end of input");
+        }
+        return Ok(Some(Err(format!(
+            "request of {} bytes exceeds the maximum of {} bytes",
+            len, max_size
+        ))));
+    }
+    let mut request = vec![0; len as usize];
+    input
+        .read_exact(&mut request)
+        .context("failed to read request")?;
+    Ok(Some(Ok(request)))
+}
+
+/// Compile every function in `source`, producing one record per function.
+fn compile_request(isa: &dyn TargetIsa, source: &str) -> Vec<Result<Vec<u8>, String>> {
+    let funcs = match cranelift_reader::parse_functions(source) {
+        Ok(funcs) => funcs,
+        Err(err) => return vec![Err(err.to_string())],
+    };
+
+    funcs
+        .into_iter()
+        .map(|func| {
+            let mut context = Context::for_function(func);
+            let stencil = context
+                .compile_stencil(isa)
+                .map_err(|err| pretty_error(&context.func, err))?;
+            let (_, serialized) = serialize_compiled(stencil);
+            serialized.map_err(|err| format!("failed to serialize compiled code: {}", err))
+        })
+        .collect()
+}
+
+fn write_reply(output: &mut impl Write, records: &[Result<Vec<u8>, String>]) -> io::Result<()> {
+    output.write_all(&(records.len() as u32).to_le_bytes())?;
+    for record in records {
+        let (status, payload) = match record {
+            Ok(bytes) => (STATUS_OK, &bytes[..]),
+            Err(message) => (STATUS_ERROR, message.as_bytes()),
+        };
+        output.write_all(&[status])?;
+        output.write_all(&(payload.len() as u32).to_le_bytes())?;
+        output.write_all(payload)?;
+    }
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cranelift_codegen::incremental_cache::try_finish_recompile;
+    use cranelift_codegen::settings;
+    use std::convert::TryInto;
+
+    const SOURCE: &str = "
+        function %add(i32, i32) -> i32 {
+        block0(v0: i32, v1: i32):
+            v2 = iadd v0, v1
+            return v2
+        }
+
+        function %bad() -> i32 {
+        block0:
+            return
+        }
+    ";
+
+    fn request(bytes: &[u8]) -> Vec<u8> {
+        let mut request = (bytes.len() as u32).to_le_bytes().to_vec();
+        request.extend_from_slice(bytes);
+        request
+    }
+
+    /// Split a reply into its records.
+    fn parse_reply(mut reply: &[u8]) -> (Vec<(u8, Vec<u8>)>, &[u8]) {
+        let mut take = |n: usize| {
+            let (head, tail) = reply.split_at(n);
+            reply = tail;
+            head
+        };
+        let count = u32::from_le_bytes(take(4).try_into().unwrap());
+        let records = (0..count)
+            .map(|_| {
+                let status = take(1)[0];
+                let len = u32::from_le_bytes(take(4).try_into().unwrap());
+                (status, take(len as usize).to_vec())
+            })
+            .collect();
+        (records, reply)
+    }
+
+    #[test]
+    fn round_trip() {
+        let isa = cranelift_codegen::isa::lookup_by_name("x86_64")
+            .unwrap()
+            .finish(settings::Flags::new(settings::builder()))
+            .unwrap();
+
+        let mut input = request(SOURCE.as_bytes());
+        input.extend(request(b"function %f("));
+        input.extend(request(&[0xff; 4]));
+        let mut output = vec![];
+        serve(&*isa, 16 << 10, &mut &input[..], &mut output).unwrap();
+
+        let (records, rest) = parse_reply(&output);
+        assert_eq!(records.len(), 2);
+        let funcs = cranelift_reader::parse_functions(SOURCE).unwrap();
+        let (status, payload) = &records[0];
+        assert_eq!(*status, STATUS_OK);
+        let compiled = try_finish_recompile(&funcs[0], payload).unwrap();
+        let mut context = Context::for_function(funcs[0].clone());
+        context.compile(&*isa).unwrap();
+        assert_eq!(
+            compiled.buffer.data(),
+            context.compiled_code().unwrap().buffer.data()
+        );
+        let (status, payload) = &records[1];
+        assert_eq!(*status, STATUS_ERROR);
+        assert!(String::from_utf8_lossy(payload).contains("return"));
+
+        // A request that doesn't parse gets a single error record.
+        let (records, rest) = parse_reply(rest);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, STATUS_ERROR);
+
+        // So does one that isn't UTF-8.
+        let (records, rest) = parse_reply(rest);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, STATUS_ERROR);
+        assert!(String::from_utf8_lossy(&records[0].1).contains("UTF-8"));
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn oversized_request() {
+        let isa = cranelift_codegen::isa::lookup_by_name("x86_64")
+            .unwrap()
+            .finish(settings::Flags::new(settings::builder()))
+            .unwrap();
+
+        let mut input = request(SOURCE.as_bytes());
+        input.extend(request(b"function %f() {\nblock0:\n    return\n}"));
+        let mut output = vec![];
+        serve(&*isa, 64, &mut &input[..], &mut output).unwrap();
+
+        // The oversized request is rejected without affecting the next one.
+        let (records, rest) = parse_reply(&output);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, STATUS_ERROR);
+        assert!(String::from_utf8_lossy(&records[0].1).contains("exceeds the maximum"));
+        let (records, rest) = parse_reply(rest);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, STATUS_OK);
+        assert!(rest.is_empty());
+
+        // A truncated oversized request is an error, as is any truncated request.
+        let mut input = (1024u32).to_le_bytes().to_vec();
+        input.extend([0; 100]);
+        assert!(serve(&*isa, 64, &mut &input[..], &mut vec![]).is_err());
+    }
+}
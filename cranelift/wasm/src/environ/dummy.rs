@@ -10,8 +10,8 @@ use crate::func_translator::FuncTranslator;
 use crate::state::FuncTranslationState;
 use crate::WasmType;
 use crate::{
-    DataIndex, DefinedFuncIndex, ElemIndex, FuncIndex, Global, GlobalIndex, Memory, MemoryIndex,
-    Table, TableIndex, TypeIndex, WasmFuncType, WasmResult,
+    DataIndex, DefinedFuncIndex, ElemIndex, FuncIndex, Global, GlobalIndex, HashMap, Memory,
+    MemoryIndex, Table, TableIndex, TypeIndex, WasmFuncType, WasmResult,
 };
 use core::convert::TryFrom;
 use cranelift_codegen::cursor::FuncCursor;
@@ -44,6 +44,36 @@ impl<T> Exportable<T> {
     }
 }
 
+/// An active data segment, copied into a memory at instantiation.
+pub struct DataInitializer {
+    /// The memory to initialize.
+    pub memory_index: MemoryIndex,
+
+    /// A global whose value is added to `offset`, if any.
+    pub base: Option<GlobalIndex>,
+
+    /// The offset in the memory at which to place the data.
+    pub offset: u64,
+
+    /// The bytes to copy.
+    pub data: Vec<u8>,
+}
+
+/// An active element segment, copied into a table at instantiation.
+pub struct TableElements {
+    /// The table to initialize.
+    pub table_index: TableIndex,
+
+    /// A global whose value is added to `offset`, if any.
+    pub base: Option<GlobalIndex>,
+
+    /// The index in the table at which to place the elements.
+    pub offset: u32,
+
+    /// The functions to place in the table.
+    pub elements: Box<[FuncIndex]>,
+}
+
 /// The main state belonging to a `DummyEnvironment`. This is split out from
 /// `DummyEnvironment` to allow it to be borrowed separately from the
 /// `FuncTranslator` field.
@@ -83,6 +113,18 @@ pub struct DummyModuleInfo {
 
     /// The start function.
     pub start_func: Option<FuncIndex>,
+
+    /// Active data segments, in declaration order.
+    pub data_initializers: Vec<DataInitializer>,
+
+    /// Passive data segments, for `memory.init`.
+    pub passive_data: HashMap<DataIndex, Vec<u8>>,
+
+    /// Active element segments, in declaration order.
+    pub table_elements: Vec<TableElements>,
+
+    /// Passive element segments, for `table.init`.
+    pub passive_elements: HashMap<ElemIndex, Box<[FuncIndex]>>,
 }
 
 impl DummyModuleInfo {
@@ -101,6 +143,10 @@ impl DummyModuleInfo {
             memories: PrimaryMap::new(),
             globals: PrimaryMap::new(),
             start_func: None,
+            data_initializers: Vec::new(),
+            passive_data: HashMap::new(),
+            table_elements: Vec::new(),
+            passive_elements: HashMap::new(),
         }
     }
 }
@@ -736,28 +782,31 @@ impl<'data> ModuleEnvironment<'data> for DummyEnvironment {
 
     fn declare_table_elements(
         &mut self,
-        _table_index: TableIndex,
-        _base: Option<GlobalIndex>,
-        _offset: u32,
-        _elements: Box<[FuncIndex]>,
+        table_index: TableIndex,
+        base: Option<GlobalIndex>,
+        offset: u32,
+        elements: Box<[FuncIndex]>,
     ) -> WasmResult<()> {
-        // We do nothing
+        self.info.table_elements.push(TableElements {
+            table_index,
+            base,
+            offset,
+            elements,
+        });
         Ok(())
     }
 
     fn declare_passive_element(
         &mut self,
-        _elem_index: ElemIndex,
-        _segments: Box<[FuncIndex]>,
+        elem_index: ElemIndex,
+        segments: Box<[FuncIndex]>,
     ) -> WasmResult<()> {
+        self.info.passive_elements.insert(elem_index, segments);
         Ok(())
     }
 
-    fn declare_passive_data(
-        &mut self,
-        _elem_index: DataIndex,
-        _segments: &'data [u8],
-    ) -> WasmResult<()> {
+    fn declare_passive_data(&mut self, data_index: DataIndex, data: &'data [u8]) -> WasmResult<()> {
+        self.info.passive_data.insert(data_index, data.to_vec());
         Ok(())
     }
 
@@ -781,12 +830,17 @@ impl<'data> ModuleEnvironment<'data> for DummyEnvironment {
 
     fn declare_data_initialization(
         &mut self,
-        _memory_index: MemoryIndex,
-        _base: Option<GlobalIndex>,
-        _offset: u64,
-        _data: &'data [u8],
+        memory_index: MemoryIndex,
+        base: Option<GlobalIndex>,
+        offset: u64,
+        data: &'data [u8],
     ) -> WasmResult<()> {
-        // We do nothing
+        self.info.data_initializers.push(DataInitializer {
+            memory_index,
+            base,
+            offset,
+            data: data.to_vec(),
+        });
         Ok(())
     }
 
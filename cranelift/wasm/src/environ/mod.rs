@@ -4,7 +4,7 @@ mod dummy;
 #[macro_use]
 mod spec;
 
-pub use crate::environ::dummy::DummyEnvironment;
+pub use crate::environ::dummy::{DataInitializer, DummyEnvironment, TableElements};
 pub use crate::environ::spec::{
     FuncEnvironment, GlobalVariable, ModuleEnvironment, TargetEnvironment,
 };
@@ -8,7 +8,7 @@ use crate::code_translator::{bitcast_wasm_returns, translate_operator};
 use crate::environ::FuncEnvironment;
 use crate::state::FuncTranslationState;
 use crate::translation_utils::get_vmctx_value_label;
-use crate::WasmResult;
+use crate::{WasmError, WasmResult};
 use core::convert::TryInto;
 use cranelift_codegen::entity::EntityRef;
 use cranelift_codegen::ir::{self, Block, InstBuilder, ValueLabel};
@@ -24,6 +24,7 @@ use wasmparser::{self, BinaryReader, FuncValidator, FunctionBody, WasmModuleReso
 pub struct FuncTranslator {
     func_ctx: FunctionBuilderContext,
     state: FuncTranslationState,
+    max_insts: Option<usize>,
 }
 
 impl FuncTranslator {
@@ -32,9 +33,19 @@ impl FuncTranslator {
         Self {
             func_ctx: FunctionBuilderContext::new(),
             state: FuncTranslationState::new(),
+            max_insts: None,
         }
     }
 
+    /// Limit the number of Cranelift IR instructions a single function may translate into.
+    ///
+    /// Translation fails with `WasmError::ImplLimitExceeded` as soon as the limit is exceeded,
+    /// which bounds the memory spent on pathological function bodies. There is no limit by
+    /// default.
+    pub fn set_max_insts(&mut self, max_insts: Option<usize>) {
+        self.max_insts = max_insts;
+    }
+
     /// Returns the underlying `FunctionBuilderContext` that this translator
     /// uses.
     pub fn context(&mut self) -> &mut FunctionBuilderContext {
@@ -115,7 +126,14 @@ impl FuncTranslator {
         self.state.initialize(&builder.func.signature, exit_block);
 
         parse_local_decls(&mut reader, &mut builder, num_params, environ, validator)?;
-        parse_function_body(validator, reader, &mut builder, &mut self.state, environ)?;
+        parse_function_body(
+            validator,
+            reader,
+            &mut builder,
+            &mut self.state,
+            environ,
+            self.max_insts,
+        )?;
 
         builder.finalize();
         Ok(())
@@ -228,6 +246,7 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
     builder: &mut FunctionBuilder,
     state: &mut FuncTranslationState,
     environ: &mut FE,
+    max_insts: Option<usize>,
 ) -> WasmResult<()> {
     // The control stack is initialized with a single block representing the whole function.
     debug_assert_eq!(state.control_stack.len(), 1, "State not initialized");
@@ -241,6 +260,9 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
         environ.before_translate_operator(&op, builder, state)?;
         translate_operator(validator, &op, builder, state, environ)?;
         environ.after_translate_operator(&op, builder, state)?;
+        if matches!(max_insts, Some(max) if builder.func.dfg.num_insts() > max) {
+            return Err(WasmError::ImplLimitExceeded);
+        }
     }
     environ.after_translate_function(builder, state)?;
     let pos = reader.original_position();
@@ -402,6 +424,44 @@ mod tests {
         ctx.verify(&flags).unwrap();
     }
 
+    #[test]
+    fn max_insts() {
+        let wasm = wat::parse_str(
+            "
+                (module
+                    (func $max_insts (param i32) (result i32)
+                        (i32.add (i32.add (get_local 0) (i32.const 1)) (i32.const 2))
+                    )
+                )
+            ",
+        )
+        .unwrap();
+
+        let runtime = DummyEnvironment::new(
+            isa::TargetFrontendConfig {
+                default_call_conv: isa::CallConv::Fast,
+                pointer_width: PointerWidth::U64,
+            },
+            false,
+        );
+        let translate = |max_insts| {
+            let mut trans = FuncTranslator::new();
+            trans.set_max_insts(max_insts);
+            let mut func = ir::Function::new();
+            func.signature.params.push(ir::AbiParam::new(I32));
+            func.signature.returns.push(ir::AbiParam::new(I32));
+            let (body, mut validator) = extract_func(&wasm);
+            trans.translate_body(&mut validator, body, &mut func, &mut runtime.func_env())
+        };
+
+        assert!(translate(None).is_ok());
+        assert!(translate(Some(100)).is_ok());
+        assert!(matches!(
+            translate(Some(2)),
+            Err(crate::WasmError::ImplLimitExceeded)
+        ));
+    }
+
     fn extract_func(wat: &[u8]) -> (FunctionBody<'_>, FuncValidator<ValidatorResources>) {
         let mut validator = Validator::new();
         for payload in Parser::new(0).parse_all(wat) {
@@ -2,7 +2,10 @@ use cranelift_codegen::isa::{CallConv, TargetFrontendConfig};
 use cranelift_codegen::print_errors::pretty_verifier_error;
 use cranelift_codegen::settings::{self, Flags};
 use cranelift_codegen::verifier;
-use cranelift_wasm::{translate_module, DummyEnvironment, FuncIndex};
+use cranelift_wasm::{
+    translate_module, DataIndex, DataInitializer, DummyEnvironment, ElemIndex, FuncIndex,
+    TableElements,
+};
 use std::fs;
 use std::path::Path;
 use target_lexicon::PointerWidth;
@@ -59,6 +62,56 @@ fn use_name_section() {
     );
 }
 
+#[test]
+fn segments_and_start() {
+    let data = wat::parse_str(
+        r#"
+        (module
+            (memory 1)
+            (table 2 funcref)
+            (func $start)
+            (func $f)
+            (start $start)
+            (data (i32.const 16) "hello")
+            (data "passive")
+            (elem (i32.const 1) $f $start)
+            (elem func $f)
+        )"#,
+    )
+    .unwrap();
+
+    let mut dummy_environ = DummyEnvironment::new(
+        TargetFrontendConfig {
+            default_call_conv: CallConv::SystemV,
+            pointer_width: PointerWidth::U64,
+        },
+        false,
+    );
+
+    translate_module(data.as_ref(), &mut dummy_environ).unwrap();
+    let info = &dummy_environ.info;
+
+    assert_eq!(info.start_func, Some(FuncIndex::from_u32(0)));
+
+    assert_eq!(info.data_initializers.len(), 1);
+    let data: &DataInitializer = &info.data_initializers[0];
+    assert_eq!(data.offset, 16);
+    assert_eq!(data.data, b"hello");
+    assert_eq!(info.passive_data[&DataIndex::from_u32(1)], b"passive");
+
+    assert_eq!(info.table_elements.len(), 1);
+    let elements: &TableElements = &info.table_elements[0];
+    assert_eq!(elements.offset, 1);
+    assert_eq!(
+        &elements.elements[..],
+        &[FuncIndex::from_u32(1), FuncIndex::from_u32(0)]
+    );
+    assert_eq!(
+        &info.passive_elements[&ElemIndex::from_u32(1)][..],
+        &[FuncIndex::from_u32(1)]
+    );
+}
+
 fn read_module(path: &Path) -> Vec<u8> {
     match path.extension() {
         None => {
@@ -573,6 +573,7 @@ mod host {
                         params,
                         returns,
                         call_conv: crate::wasmtime_call_conv(&*compiler.isa),
+                        variadic: None,
                     });
 
                     (sig, offsets::$name)
@@ -91,6 +91,7 @@ macro_rules! declare_function_signatures {
                             params: vec![ $( self.$param() ),* ],
                             returns: vec![ $( self.$result() )? ],
                             call_conv: self.call_conv,
+                            variadic: None,
                         })
                     });
                     self.$name = Some(sig);
@@ -391,7 +391,9 @@ impl Engine {
             | "regalloc_verbose_logs"
             | "is_pic"
             | "machine_code_cfg_info"
+            | "align_loops"
             | "tls_model" // wasmtime doesn't use tls right now
+            | "code_model" // only changes which relocations are emitted
             | "opt_level" // opt level doesn't change semantics
             | "enable_alias_analysis" // alias analysis-based opts don't change semantics
             | "probestack_func_adjusts_sp" // probestack above asserted disabled